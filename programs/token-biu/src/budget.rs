@@ -0,0 +1,142 @@
+//! Pool budgets the sale pays out of, and the inventory they are sized against.
+//!
+//! Every payout draws from exactly one pool, so an exhausted pool can never
+//! borrow from another one.
+
+use crate::{error::ErrorCode, states::*};
+
+/// Record `token_amount` as paid out of `pool`
+pub fn draw(pool: &mut PoolBudget, token_amount: u64) -> Result<(), ErrorCode> {
+
+    if token_amount > pool.remaining() {
+        return Err(ErrorCode::PoolBudgetExceeded);
+    }
+
+    pool.drawn += token_amount;
+
+    Ok(())
+}
+
+/// Pay a claim's transfer `fee` from the fees set aside at purchase, the sale pool covers the rest
+pub fn charge_claim_fee(reserve: &mut u64, sale_pool: &mut PoolBudget, fee: u64) -> Result<(), ErrorCode> {
+
+    let from_reserve = fee.min(*reserve);
+    draw(sale_pool, fee - from_reserve)?;
+    *reserve -= from_reserve;
+
+    Ok(())
+}
+
+/// Check a new lifetime `budget` for `pool`. It has to cover what the pool already paid out,
+/// and any increase of the remaining budget has to come from the `unreserved` inventory.
+pub fn validate_pool_budget(pool: &PoolBudget, budget: u64, unreserved: u64) -> Result<(), ErrorCode> {
+
+    if budget < pool.drawn {
+        return Err(ErrorCode::InvalidPoolBudget);
+    }
+
+    let remaining = budget - pool.drawn;
+    if remaining > pool.remaining().saturating_add(unreserved) {
+        return Err(ErrorCode::InsufficientTokens);
+    }
+
+    Ok(())
+}
+
+/// Tokens an ended sale can burn: what its ledger holds, less the tokens held for vesting
+/// positions and the treasury budget still open for withdrawals
+pub fn burnable_inventory(held: u64, tokens_locked: u64, treasury_remaining: u64) -> u64 {
+
+    held.saturating_sub(tokens_locked).saturating_sub(treasury_remaining)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn draws_are_bounded_by_each_pool_budget() {
+        let mut sale = PoolBudget { budget: 100, drawn: 0, scheduled: false };
+        let mut bonus = PoolBudget { budget: 10, drawn: 0, scheduled: false };
+
+        draw(&mut sale, 60).unwrap();
+        assert_eq!(draw(&mut sale, 41), Err(ErrorCode::PoolBudgetExceeded));
+        assert_eq!(sale.remaining(), 40);
+
+        // An exhausted pool cannot borrow from another one
+        draw(&mut bonus, 10).unwrap();
+        assert_eq!(draw(&mut bonus, 1), Err(ErrorCode::PoolBudgetExceeded));
+        assert_eq!(sale.remaining(), 40);
+    }
+
+    #[test]
+    fn claim_fees_come_from_the_reserve_then_the_sale_pool() {
+        let mut reserve = 30;
+        let mut sale = PoolBudget { budget: 100, drawn: 90, scheduled: false };
+
+        assert_eq!(charge_claim_fee(&mut reserve, &mut sale, 20), Ok(()));
+        assert_eq!((reserve, sale.drawn), (10, 90));
+
+        assert_eq!(charge_claim_fee(&mut reserve, &mut sale, 15), Ok(()));
+        assert_eq!((reserve, sale.drawn), (0, 95));
+
+        // Nothing is taken when the sale pool cannot cover the rest
+        let mut reserve = 1;
+        assert_eq!(charge_claim_fee(&mut reserve, &mut sale, 10), Err(ErrorCode::PoolBudgetExceeded));
+        assert_eq!((reserve, sale.drawn), (1, 95));
+    }
+
+    #[test]
+    fn pool_budget_covers_draws_and_grows_from_unreserved_inventory() {
+        let pool = PoolBudget { budget: 100, drawn: 60, scheduled: false };
+
+        assert_eq!(validate_pool_budget(&pool, 59, 1_000), Err(ErrorCode::InvalidPoolBudget));
+        assert_eq!(validate_pool_budget(&pool, 60, 0), Ok(()));
+
+        // The current remaining budget of 40 can be reused, growth beyond it needs unreserved tokens
+        assert_eq!(validate_pool_budget(&pool, 150, 50), Ok(()));
+        assert_eq!(validate_pool_budget(&pool, 151, 50), Err(ErrorCode::InsufficientTokens));
+    }
+
+    #[test]
+    fn pool_budget_is_bounded_by_the_ledger_not_the_token_balance() {
+        let ledger = InventoryLedger { deposited: 1_000, sold: 400, ..Default::default() };
+        let pool = PoolBudget::default();
+
+        // Another 10_000 tokens sitting untracked in the token account cannot be reserved
+        assert_eq!(validate_pool_budget(&pool, ledger.held(), ledger.held()), Ok(()));
+        assert_eq!(validate_pool_budget(&pool, ledger.held() + 1, ledger.held()), Err(ErrorCode::InsufficientTokens));
+    }
+
+    #[test]
+    fn burn_keeps_vesting_and_treasury_obligations() {
+        let ledger = InventoryLedger { deposited: 1_000, sold: 200, ..Default::default() };
+
+        assert_eq!(burnable_inventory(ledger.held(), 300, 100), 400);
+
+        // A foreign sale's ledger holds nothing, however much sits in another sale's account
+        assert_eq!(burnable_inventory(InventoryLedger::default().held(), 0, 0), 0);
+
+        // Obligations larger than the inventory leave nothing to burn
+        assert_eq!(burnable_inventory(ledger.held(), 700, 200), 0);
+    }
+
+    #[test]
+    fn ledger_holds_only_tracked_inventory() {
+        let mut ledger = InventoryLedger { deposited: 1_000, minted: 200, ..Default::default() };
+        ledger.sold = 300;
+        ledger.claimed = 100;
+        ledger.withdrawn = 50;
+
+        assert_eq!(ledger.held(), 750);
+
+        // Tokens sent straight to the token account show up as a discrepancy, never as inventory
+        let balance: u64 = 5_750;
+        assert_eq!(balance as i128 - ledger.expected_balance(), 5_000);
+        assert_eq!(ledger.held(), 750);
+
+        // Recorded outflows beyond the inflows leave nothing to hold
+        ledger.burned = 10_000;
+        assert_eq!(ledger.held(), 0);
+    }
+}
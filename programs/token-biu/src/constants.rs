@@ -5,65 +5,51 @@ pub const SOL_USD_FEED_ID: &str =
     "0xef0d8b6fda2ceba41da15d4095d1da392a0d2f8ed0c6c7bc0f4cfac8c280b56d";
 
 pub const SALE_AUTHORITY: &[u8] = b"SALE_AUTHORITY";
+pub const MONTHLY_LIMITS: &[u8] = b"monthly_limits_a";
+pub const BUYER_VESTING: &[u8] = b"buyer_vesting";
+pub const GRANT: &[u8] = b"grant";
+pub const GRANT_VAULT: &[u8] = b"grant_vault";
+pub const PROCEEDS_VAULT: &[u8] = b"proceeds_vault";
+pub const REFERRER_STATS: &[u8] = b"referrer_stats";
+pub const EVENT_AUTHORITY: &[u8] = b"__event_authority"; // Fixed by `#[event_cpi]`
 
 pub const ANCHOR_DISCRIMINATOR: usize = 8;
 pub const MAX_AGE: u64 = 100;
 pub const PRICE_FEED_DECIMAL_ADJUSTMENT: u128 = 10;
 pub const SECONDS_IN_A_DAY: i64 = 24 * 60 * 60;
-
-// Define an enum for the periods
-#[derive(Debug, Clone, Copy)]
-pub enum Period {
-    Hourly,
-    Daily,
-    Monthly,
-}
-
-impl Period {
-    // Method to get the length of the period in seconds
-    pub const  fn length(&self) -> i64 {
-        match self {
-            Period::Hourly => 3600,
-            Period::Daily => 86400,
-            Period::Monthly => 2629743,
-        }
-    }
-
-    // Method to get the count of periods in a year
-    pub const fn count(&self) -> i64 {
-        match self {
-            Period::Hourly => 12,
-            Period::Daily => 12,
-            Period::Monthly => 12,
-        }
-    }
-}
-
-// Use the enum to define the constants
-
-/*
-
-Period::Monthly => For monthly periods ~30.44 days
-Period::Daily => For daily periods - 24 hours
-Period::Hourly => For hourly periods - 1 hour
-
-*/
-
-pub const PERIOD: Period = Period::Monthly;
-
-pub const PERIOD_LENGTH: i64 = PERIOD.length();
-pub const PERIOD_COUNT: i64 = PERIOD.count();
+pub const BPS_DENOMINATOR: u64 = 10_000;
+pub const MAX_CLAIM_REWARD_BPS: u16 = 100; // Crankers earn at most 1% of a claim
+pub const MAX_RECIPIENTS: usize = 5;
+pub const MAX_REFERRAL_BPS: u16 = 1_000; // Referrers earn at most 10% of a purchase
+pub const MAX_BONUS_WINDOWS: usize = 4;
+pub const MAX_BONUS_TIERS: usize = 4;
+pub const POOL_COUNT: usize = 4; // One budget per PoolKind
+pub const MAX_TREASURY_DESTINATIONS: usize = 5;
+pub const MAX_WITHDRAWAL_REASON_LEN: usize = 128; // Bytes of the reason recorded with an admin withdrawal
 
 pub const DEFAULT: u64 = 0;
 pub const MONTHS_IN_A_YEAR: u8 = 12;
+pub const MAX_TRANCHES: usize = u8::MAX as usize; // Bounded by the u8 last_checked_index
 
 // Decimal constants
 pub const SOL_DECIMALS: f64 = 9.0; // SOL has 9 decimal places
 
 // Space constants
-pub const MONTHLY_LIMITS_SIZE: usize = 8 + (8 * 14) + (8 * 14) + 8 + 8 + 8 + 1 + 1 ; // Size of MonthlyLimits account
-pub const WALLET_PURCHASE_SIZE: usize = 8 + 32 + 8 + 8 + 1; // Size of WalletPurchase account
-pub const SALE_CONFIG_SIZE: usize = 8 + 32 + 32 + 32 + 32 + 8 + 8 + 8 + 1 + 1; // Size of SaleConfig account
+pub const MONTHLY_LIMITS_BASE_SIZE: usize = 8 + 32 + 1 + 4 + 4 + 8 + 8 + 8 + 1 + 1 + 1 + 2 + 8; // Size of an empty MonthlyLimits account
+pub const TRANCHE_SIZE: usize = 8 + 8; // One timestamp and one limit
+pub const WALLET_PURCHASE_SIZE: usize = 8 + 32 + 8 + 8 + 1 + 8 + 8 + 32; // Size of WalletPurchase account
+pub const SALE_CONFIG_SIZE: usize = 8 + 32 + 32 + 32 + 32 + 8 + 8 + 8 + 1 + 1 + BUYER_VESTING_CONFIG_SIZE + 8 + 2 + 1 + 1 + 1 + 32 + 8 + 8 + 8 + (RECIPIENT_SHARE_SIZE * MAX_RECIPIENTS) + 1 + REFERRAL_CONFIG_SIZE + BONUS_CONFIG_SIZE + (POOL_BUDGET_SIZE * POOL_COUNT) + 1 + 8 + INVENTORY_LEDGER_SIZE + 8 + 1 + (32 * MAX_TREASURY_DESTINATIONS) + 1 + 8 + 8 + 8; // Size of SaleConfig account
+pub const POOL_BUDGET_SIZE: usize = 8 + 8 + 1; // Size of PoolBudget
+pub const INVENTORY_LEDGER_SIZE: usize = 8 * 7; // Size of InventoryLedger
+pub const RECIPIENT_SHARE_SIZE: usize = 32 + 2; // Size of RecipientShare
+pub const BONUS_CONFIG_SIZE: usize = 1 + (BONUS_WINDOW_SIZE * MAX_BONUS_WINDOWS) + 1 + (VOLUME_TIER_SIZE * MAX_BONUS_TIERS) + 1 + 1; // Size of BonusConfig
+pub const BONUS_WINDOW_SIZE: usize = 8 + 8 + 2; // Size of BonusWindow
+pub const VOLUME_TIER_SIZE: usize = 8 + 2; // Size of VolumeTier
+pub const REFERRAL_CONFIG_SIZE: usize = 1 + 2 + 1 + 8; // Size of ReferralConfig
+pub const REFERRER_STATS_SIZE: usize = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 8; // Size of ReferrerStats account
+pub const BUYER_VESTING_CONFIG_SIZE: usize = 1 + 2 + 8 + 8 + 8; // Size of BuyerVestingConfig
+pub const BUYER_VESTING_SIZE: usize = 8 + 32 + 32 + 8 + 8 + 2; // Size of BuyerVesting account
+pub const GRANT_SIZE: usize = 8 + 32 + 32 + 8 + 8 + 8 + 8 + 1 + 8 + 8 + 8 + 1 + 1 + 2; // Size of Grant account
 
 // Vesting schedule constants
 pub const YEAR1_START: i64 = 1735689600; // March 1, 2025
@@ -71,6 +57,8 @@ pub const YEAR1_END: i64 = 1767225600;   // March 1, 2026
 pub const YEAR2_END: i64 = 1783296000;   // September 1, 2026
 pub const MONTHLY_UNLOCK: u64 = 2_660_000; // 2.66 million $LAL per month
 pub const BULK_UNLOCK: u64 = 64_000_000;   // 64 million $LAL
+pub const GRANT_MONTHLY_SPAN: i64 = YEAR1_END - YEAR1_START; // Monthly unlocks run over the first year of a grant
+pub const GRANT_BULK_DELAY: i64 = YEAR2_END - YEAR1_START;   // The bulk unlock follows six months later
 pub const MARCH: i64 = 3;
 pub const SEPTEMBER: u8 = 9;
 pub const FIRST_HALF: usize = 12;
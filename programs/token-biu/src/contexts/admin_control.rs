@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
-use crate::{events::*, states::*};
+use crate::{constants::*, events::*, states::*, error::ErrorCode, vesting, proceeds};
 
+#[event_cpi]
 #[derive(Accounts)]
 
     pub struct AdminControl<'info> {
@@ -12,53 +13,240 @@ use crate::{events::*, states::*};
 }
 
 impl<'info> AdminControl<'info> {
-    pub fn change_recipient(&mut self, new_recipient: Pubkey) -> Result<()> {
+    pub fn change_recipient(&mut self, new_recipient: Pubkey, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
+
+        proceeds::validate_recipient(&new_recipient, &self.sale_config.sale_authority)?;
 
         let old_recipient = self.sale_config.recipient;
         self.sale_config.recipient = new_recipient;
 
-        emit!(RecipientChanged {
+        emit_cpi_event(&self.event_authority, event_authority_bump, RecipientChanged {
+            header: EventHeader::new(&self.sale_config, self.authority.key())?,
             old_recipient,
             new_recipient,
-        });
+        })?;
 
         Ok(())
     }
 
-    pub fn change_authority(&mut self, new_authority: Pubkey) -> Result<()> {
+    pub fn change_authority(&mut self, new_authority: Pubkey, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
 
         let old_authority = self.sale_config.authority;
         self.sale_config.authority = new_authority;
 
-        emit!(TokenAuthorityChanged {
+        emit_cpi_event(&self.event_authority, event_authority_bump, TokenAuthorityChanged {
+            header: EventHeader::new(&self.sale_config, self.authority.key())?,
             old_authority,
             new_authority,
-        });
+        })?;
 
         Ok(())
     }
 
-    pub fn set_limit(&mut self, new_limit: u64) -> Result<()> {
+    pub fn set_limit(&mut self, new_limit: u64, event_authority_bump: u8) -> Result<()> {
 
+        self.sale_config.advance_event_seq()?;
+
+        let old_limit = self.sale_config.wallet_purchase_limit;
         self.sale_config.wallet_purchase_limit = new_limit;
 
-        emit!(WalletLimitSet {
-            new_limit
-        });
+        emit_cpi_event(&self.event_authority, event_authority_bump, WalletLimitSet {
+            header: EventHeader::new(&self.sale_config, self.authority.key())?,
+            old_limit,
+            new_limit,
+        })?;
+
+        Ok(())
+    }
+
+    pub fn pause(&mut self, event_authority_bump: u8) -> Result<()> {
+
+        self.set_paused(true, event_authority_bump)
+    }
+
+    pub fn resume(&mut self, event_authority_bump: u8) -> Result<()> {
+
+        self.set_paused(false, event_authority_bump)
+    }
+
+    /// Set the pause flag, recording the previous one
+    fn set_paused(&mut self, paused: bool, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
+
+        let old_paused = self.sale_config.paused;
+        self.sale_config.paused = paused;
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, SalePauseSet {
+            header: EventHeader::new(&self.sale_config, self.authority.key())?,
+            old_paused,
+            new_paused: paused,
+        })?;
+
+        Ok(())
+    }
+
+    pub fn set_sale_end(&mut self, end_timestamp: i64, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
+
+        require!(!self.sale_config.finalized, ErrorCode::SaleAlreadyFinalized);
+        require!(end_timestamp >= 0, ErrorCode::InvalidTimestamp);
+
+        // An ended sale may already have burned its unsold pools, it cannot be reopened
+        require!(
+            !self.sale_config.has_ended(Clock::get()?.unix_timestamp) && self.sale_config.inventory.burned == 0,
+            ErrorCode::SaleEnded
+        );
+
+        let old_end_timestamp = self.sale_config.end_timestamp;
+        self.sale_config.end_timestamp = end_timestamp;
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, SaleEndSet {
+            header: EventHeader::new(&self.sale_config, self.authority.key())?,
+            old_end_timestamp,
+            new_end_timestamp: end_timestamp,
+        })?;
 
         Ok(())
     }
 
-    pub fn pause(&mut self) -> Result<()> {
+    /// Close the sale for good, purchases are refused from now on
+    pub fn finalize(&mut self, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
 
-        self.sale_config.paused = true;
+        require!(!self.sale_config.finalized, ErrorCode::SaleAlreadyFinalized);
+
+        self.sale_config.finalized = true;
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, SaleFinalized {
+            header: EventHeader::new(&self.sale_config, self.authority.key())?,
+            total_raised: self.sale_config.total_raised,
+        })?;
 
         Ok(())
     }
 
-    pub fn resume(&mut self) -> Result<()> {
+    pub fn set_buyer_vesting(&mut self, config: BuyerVestingConfig, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
+
+        require!(config.tge_bps as u64 <= BPS_DENOMINATOR, ErrorCode::InvalidBuyerVesting);
+        require!(config.cliff_seconds >= 0 && config.duration_seconds >= 0, ErrorCode::InvalidBuyerVesting);
+        require!(!config.enabled || config.start_timestamp > 0, ErrorCode::InvalidBuyerVesting);
+
+        vesting::validate_buyer_vesting_update(&self.sale_config.buyer_vesting, &config, self.sale_config.tokens_locked)?;
+
+        let old_config = self.sale_config.buyer_vesting;
+        self.sale_config.buyer_vesting = config;
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, BuyerVestingSet {
+            header: EventHeader::new(&self.sale_config, self.authority.key())?,
+            old_config,
+            new_config: config,
+        })?;
+
+        Ok(())
+    }
+
+    pub fn set_claim_reward(&mut self, claim_reward_bps: u16, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
+
+        require!(claim_reward_bps <= MAX_CLAIM_REWARD_BPS, ErrorCode::InvalidClaimReward);
+
+        let old_claim_reward_bps = self.sale_config.claim_reward_bps;
+        self.sale_config.claim_reward_bps = claim_reward_bps;
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, ClaimRewardSet {
+            header: EventHeader::new(&self.sale_config, self.authority.key())?,
+            old_claim_reward_bps,
+            new_claim_reward_bps: claim_reward_bps,
+        })?;
+
+        Ok(())
+    }
+
+    pub fn set_positions_transferable(&mut self, positions_transferable: bool, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
+
+        let old_positions_transferable = self.sale_config.positions_transferable;
+        self.sale_config.positions_transferable = positions_transferable;
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, PositionTransferabilitySet {
+            header: EventHeader::new(&self.sale_config, self.authority.key())?,
+            old_positions_transferable,
+            new_positions_transferable: positions_transferable,
+        })?;
+
+        Ok(())
+    }
+
+    pub fn set_recipients(&mut self, recipients: Vec<RecipientShare>, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
+
+        require!(recipients.len() <= MAX_RECIPIENTS, ErrorCode::InvalidRecipientTable);
+
+        let total_bps = recipients.iter().map(|share| share.bps as u64).sum::<u64>();
+        require!(recipients.is_empty() || total_bps == BPS_DENOMINATOR, ErrorCode::InvalidRecipientTable);
+
+        for (i, share) in recipients.iter().enumerate() {
+            proceeds::validate_recipient(&share.wallet, &self.sale_config.sale_authority)?;
+            require!(
+                recipients[..i].iter().all(|other| other.wallet != share.wallet),
+                ErrorCode::InvalidRecipientTable
+            );
+        }
+
+        let old_recipients = self.sale_config.recipient_shares().to_vec();
+
+        let mut table = [RecipientShare::default(); MAX_RECIPIENTS];
+        table[..recipients.len()].copy_from_slice(&recipients);
+
+        self.sale_config.recipients = table;
+        self.sale_config.recipient_count = recipients.len() as u8;
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, RecipientsSet {
+            header: EventHeader::new(&self.sale_config, self.authority.key())?,
+            old_recipients,
+            new_recipients: recipients,
+        })?;
+
+        Ok(())
+    }
+
+    /// Replace the token accounts `withdraw_tokens` may send to, an empty list blocks admin withdrawals
+    pub fn set_treasury_destinations(&mut self, destinations: Vec<Pubkey>, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
+
+        require!(destinations.len() <= MAX_TREASURY_DESTINATIONS, ErrorCode::InvalidTreasuryDestinations);
+
+        for (i, destination) in destinations.iter().enumerate() {
+            require!(*destination != Pubkey::default(), ErrorCode::InvalidTreasuryDestinations);
+            require!(!destinations[..i].contains(destination), ErrorCode::InvalidTreasuryDestinations);
+        }
+
+        let old_destinations = self.sale_config.allowed_destinations().to_vec();
+
+        let mut table = [Pubkey::default(); MAX_TREASURY_DESTINATIONS];
+        table[..destinations.len()].copy_from_slice(&destinations);
+
+        self.sale_config.treasury_destinations = table;
+        self.sale_config.treasury_destination_count = destinations.len() as u8;
 
-        self.sale_config.paused = false;
+        emit_cpi_event(&self.event_authority, event_authority_bump, TreasuryDestinationsSet {
+            header: EventHeader::new(&self.sale_config, self.authority.key())?,
+            old_destinations,
+            new_destinations: destinations,
+        })?;
 
         Ok(())
     }
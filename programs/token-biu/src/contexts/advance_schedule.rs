@@ -0,0 +1,42 @@
+use anchor_lang::prelude::*;
+use crate::{states::*, events::*, constants::*, vesting};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AdvanceSchedule<'info> {
+
+    pub cranker: Signer<'info>,
+
+    #[account(mut)]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    #[account(
+        mut,
+        seeds = [MONTHLY_LIMITS, sale_config.key().as_ref(), &[monthly_limits.pool as u8]],
+        bump,
+    )]
+    pub monthly_limits: Box<Account<'info, MonthlyLimits>>,
+}
+
+impl<'info> AdvanceSchedule<'info> {
+    /// Credit every tranche that has unlocked since the last purchase or withdrawal.
+    /// A schedule with vesting disabled does not limit its pool and is left as it is.
+    pub fn advance(&mut self, event_authority_bump: u8) -> Result<()> {
+
+        if !self.monthly_limits.is_vesting_enabled {
+            return Ok(());
+        }
+
+        self.sale_config.advance_event_seq()?;
+
+        let current_timestamp = Clock::get()?.unix_timestamp;
+
+        let inventory = self.sale_config.pool(self.monthly_limits.pool).remaining();
+        let unlock = vesting::advance(&self.monthly_limits, current_timestamp, inventory)?;
+        unlock.apply(&mut self.monthly_limits)?;
+
+        emit_unlock(&self.event_authority, event_authority_bump, EventHeader::new(&self.sale_config, self.cranker.key())?, &unlock, &self.monthly_limits)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,67 @@
+use anchor_lang::prelude::*;
+use crate::{states::*, events::*, constants::*, error::ErrorCode, vesting};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AmendSchedule<'info> {
+
+    #[account(mut, has_one = authority)]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    #[account(
+        mut,
+        seeds = [MONTHLY_LIMITS, sale_config.key().as_ref(), &[monthly_limits.pool as u8]],
+        bump,
+    )]
+    pub monthly_limits: Box<Account<'info, MonthlyLimits>>,
+
+    pub authority: Signer<'info>,
+}
+
+impl<'info> AmendSchedule<'info> {
+    /// Replace the limits of future tranches, starting at `start_index`, keeping all counters
+    pub fn amend(&mut self, start_index: u8, limits: Vec<u64>, options: ScheduleOptions, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
+
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        let start = start_index as usize;
+        let end = start.checked_add(limits.len()).ok_or(ErrorCode::MathOverflow)?;
+
+        require!(!limits.is_empty(), ErrorCode::EmptySchedule);
+        require!(end <= self.monthly_limits.limits.len(), ErrorCode::TrancheOutOfRange);
+
+        // Tranches up to the last checked index have already been credited
+        require!(start_index > self.monthly_limits.last_checked_index, ErrorCode::TrancheAlreadyUnlocked);
+        require!(self.monthly_limits.timestamps[start] > current_timestamp, ErrorCode::TrancheAlreadyUnlocked);
+
+        let old_limits = self.monthly_limits.limits[start..end].to_vec();
+        let mut new_limits = self.monthly_limits.limits.clone();
+        new_limits[start..end].copy_from_slice(&limits);
+
+        // Tokens already released under this schedule have left the pool
+        let inventory = self.sale_config.pool(self.monthly_limits.pool).remaining()
+            .checked_add(self.monthly_limits.tokens_unlocked)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        vesting::validate_schedule(
+            &self.monthly_limits.timestamps,
+            &new_limits,
+            current_timestamp,
+            inventory,
+            &ScheduleOptions { allow_past_start: true, ..options },
+        )?;
+
+        self.monthly_limits.limits = new_limits;
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, ScheduleAmended {
+            header: EventHeader::new(&self.sale_config, self.authority.key())?,
+            pool: self.monthly_limits.pool,
+            start_index,
+            old_limits,
+            new_limits: limits,
+        })?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,83 @@
+use anchor_lang::prelude::*;
+use crate::{states::*, events::*, constants::*, error::ErrorCode, vesting};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(limits: Vec<u64>)]
+pub struct AppendTranches<'info> {
+
+    #[account(mut, has_one = authority)]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    #[account(
+        mut,
+        seeds = [MONTHLY_LIMITS, sale_config.key().as_ref(), &[monthly_limits.pool as u8]],
+        bump,
+        realloc = MonthlyLimits::space(monthly_limits.limits.len() + limits.len()),
+        realloc::payer = authority,
+        realloc::zero = false,
+    )]
+    pub monthly_limits: Box<Account<'info, MonthlyLimits>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> AppendTranches<'info> {
+    pub fn append(&mut self, limits: Vec<u64>, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
+
+        require!(!limits.is_empty(), ErrorCode::EmptySchedule);
+
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        let last_timestamp = *self.monthly_limits.timestamps.last().ok_or(ErrorCode::ScheduleNotSet)?;
+        let total_tranches = self.monthly_limits.limits.len() + limits.len();
+
+        // New tranches continue the existing cadence right after the last timestamp
+        let period_length = self.monthly_limits.period.length();
+        let start_timestamp = last_timestamp.checked_add(period_length).ok_or(ErrorCode::MathOverflow)?;
+        let timestamps = MonthlyLimits::generate_timestamps(start_timestamp, period_length, limits.len())
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        let mut new_limits = self.monthly_limits.limits.clone();
+        let mut new_timestamps = self.monthly_limits.timestamps.clone();
+        new_limits.extend_from_slice(&limits);
+        new_timestamps.extend_from_slice(&timestamps);
+
+        // Tokens already released under this schedule have left the pool
+        let inventory = self.sale_config.pool(self.monthly_limits.pool).remaining()
+            .checked_add(self.monthly_limits.tokens_unlocked)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        // Earlier tranches may already be unlocked, only the new ones are checked against the clock
+        vesting::validate_schedule(
+            &new_timestamps,
+            &new_limits,
+            current_timestamp,
+            inventory,
+            &ScheduleOptions { allow_past_start: true, ..ScheduleOptions::default() },
+        )?;
+
+        require!(timestamps[0] >= current_timestamp, ErrorCode::ScheduleStartsInPast);
+
+        self.monthly_limits.limits = new_limits;
+        self.monthly_limits.timestamps = new_timestamps;
+
+        // Past its last tranche a carry-over schedule allows the whole pool, the new tranches cap it again
+        let carried = vesting::carried_allocation(&self.monthly_limits);
+        self.monthly_limits.tokens_available = self.monthly_limits.tokens_available.min(carried);
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, TranchesAppended {
+            header: EventHeader::new(&self.sale_config, self.authority.key())?,
+            pool: self.monthly_limits.pool,
+            limits,
+            timestamps,
+            total_tranches: total_tranches as u64,
+        })?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+use crate::{states::*, events::*, constants::*, error::ErrorCode};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ConfigureBonus<'info> {
+
+    #[account(mut, has_one = authority)]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    pub authority: Signer<'info>,
+}
+
+impl<'info> ConfigureBonus<'info> {
+    /// Set the bonus windows and tiers, bonuses are paid from the bonus pool
+    pub fn configure(&mut self, config: BonusConfig, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
+
+        Self::validate(&config)?;
+
+        let old_config = self.sale_config.bonus;
+        self.sale_config.bonus = config;
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, BonusConfigSet {
+            header: EventHeader::new(&self.sale_config, self.authority.key())?,
+            old_config,
+            new_config: config,
+        })?;
+
+        Ok(())
+    }
+
+    /// Check table bounds, window ranges and tier ordering
+    fn validate(config: &BonusConfig) -> Result<()> {
+
+        require!(
+            config.window_count as usize <= MAX_BONUS_WINDOWS && config.tier_count as usize <= MAX_BONUS_TIERS,
+            ErrorCode::InvalidBonusConfig
+        );
+
+        for window in config.active_windows() {
+            require!(window.start_timestamp < window.end_timestamp, ErrorCode::InvalidBonusConfig);
+            require!(window.bonus_bps as u64 <= BPS_DENOMINATOR, ErrorCode::InvalidBonusConfig);
+        }
+
+        let tiers = config.active_tiers();
+        for tier in tiers {
+            require!(tier.bonus_bps as u64 <= BPS_DENOMINATOR, ErrorCode::InvalidBonusConfig);
+        }
+        require!(
+            tiers.windows(2).all(|pair| pair[0].min_usd < pair[1].min_usd),
+            ErrorCode::InvalidBonusConfig
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+use crate::{states::*, events::*, constants::*, error::ErrorCode, vesting, budget};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct BurnUnsold<'info> {
+
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = sale_config.token_mint == mint.key() @ ErrorCode::InvalidTokenMint
+    )]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [MONTHLY_LIMITS, sale_config.key().as_ref(), &[PoolKind::Treasury as u8]],
+        bump,
+    )]
+    pub monthly_limits: Option<Box<Account<'info, MonthlyLimits>>>,
+
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [SALE_AUTHORITY, sale_config.key().as_ref()],
+        bump = sale_config.bump,
+    )]
+    pub program_sale_authority: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = program_sale_authority,
+        associated_token::token_program = token_program,
+    )]
+    pub program_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> BurnUnsold<'info> {
+    /// Burn the inventory the sale's ledger still holds once the sale is over. The purchase pools are released
+    /// and burned, tokens held for vesting positions and the remaining treasury budget are kept.
+    /// Lowering the treasury budget first releases it for burning.
+    pub fn burn_unsold(&mut self, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
+
+        let current_timestamp = Clock::get()?.unix_timestamp;
+
+        require!(self.sale_config.has_ended(current_timestamp), ErrorCode::SaleNotEnded);
+
+        self.check_tranches_claimed(current_timestamp, event_authority_bump)?;
+
+        // Nothing can be bought once the sale has ended
+        for pool in [PoolKind::Sale, PoolKind::Bonus, PoolKind::Referral] {
+            let pool = self.sale_config.pool_mut(pool);
+            pool.budget = pool.drawn;
+        }
+
+        let unsold = budget::burnable_inventory(
+            self.sale_config.inventory.held(),
+            self.sale_config.tokens_locked.saturating_add(self.sale_config.claim_fee_reserve),
+            self.sale_config.pool(PoolKind::Treasury).remaining(),
+        ).min(self.program_token_account.amount);
+        require!(unsold > 0, ErrorCode::NothingToBurn);
+
+        self.burn_tokens(unsold)?;
+        self.sale_config.inventory.burned = self.sale_config.inventory.burned
+            .checked_add(unsold)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        self.program_token_account.reload()?;
+        self.mint.reload()?;
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, UnsoldBurned {
+            header: EventHeader::new(&self.sale_config, self.authority.key())?,
+            mint: self.mint.key(),
+            program_token_account: self.program_token_account.key(),
+            amount: unsold,
+            total_burned: self.sale_config.inventory.burned,
+            remaining_balance: self.program_token_account.amount,
+            mint_supply: self.mint.supply,
+        })?;
+
+        Ok(())
+    }
+
+    /// Treasury tranches not yet withdrawn or expired have to fit the treasury budget the burn keeps
+    fn check_tranches_claimed(&mut self, current_timestamp: i64, event_authority_bump: u8) -> Result<()> {
+
+        let Some(schedule) = self.monthly_limits.as_mut() else {
+            require!(!self.sale_config.pool(PoolKind::Treasury).scheduled, ErrorCode::PoolScheduleMissing);
+            return Ok(());
+        };
+
+        if schedule.timestamps.is_empty() {
+            return Ok(());
+        }
+
+        let inventory = self.sale_config.pool(PoolKind::Treasury).remaining();
+        let unlock = vesting::advance(schedule, current_timestamp, inventory)?;
+        unlock.apply(schedule)?;
+
+        emit_unlock(&self.event_authority, event_authority_bump, EventHeader::new(&self.sale_config, self.authority.key())?, &unlock, schedule)?;
+
+        require!(vesting::unclaimed_allocation(schedule) <= inventory, ErrorCode::TranchesUnclaimed);
+
+        Ok(())
+    }
+
+    /// Burn tokens from the program token account
+    fn burn_tokens(&self, token_amount: u64) -> Result<()> {
+
+        let sale_key = self.sale_config.key();
+        let authority_seeds: &[&[u8]] = &[
+            SALE_AUTHORITY,
+            sale_key.as_ref(),
+            &[self.sale_config.bump],
+        ];
+
+        anchor_spl::token_interface::burn(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                anchor_spl::token_interface::Burn {
+                    mint: self.mint.to_account_info(),
+                    from: self.program_token_account.to_account_info(),
+                    authority: self.program_sale_authority.to_account_info(),
+                },
+                &[authority_seeds]
+            ),
+            token_amount,
+        )?;
+
+        Ok(())
+    }
+}
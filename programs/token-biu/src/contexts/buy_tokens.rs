@@ -1,11 +1,12 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token::{Mint, Token, TokenAccount},
+    token_interface::{Mint, TokenInterface, TokenAccount},
 };
 use pyth_solana_receiver_sdk::price_update::{get_feed_id_from_hex, PriceUpdateV2};
-use crate::{states::*, events::*, constants::*, error::ErrorCode};
+use crate::{states::*, events::*, constants::*, error::ErrorCode, transfer_fee, vesting, budget, purchase};
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(sol_amount: u64)]
 pub struct BuyTokens<'info> {
@@ -18,13 +19,14 @@ pub struct BuyTokens<'info> {
 
     #[account(
         mut,
-        seeds = [SALE_AUTHORITY],
+        seeds = [SALE_AUTHORITY, sale_config.key().as_ref()],
         bump = sale_config.bump,
     )]
     pub program_sale_authority: SystemAccount<'info>,
 
 
     #[account(
+        mut,
         constraint = sale_config.authority == authority.key() @ ErrorCode::Unauthorized,
         constraint = sale_config.sale_authority == program_sale_authority.key() @ ErrorCode::WrongProgramAuthority,
         constraint = sale_config.recipient == sale_authority.key() @ ErrorCode::WrongRecipientAddress,
@@ -35,64 +37,144 @@ pub struct BuyTokens<'info> {
     /// CHECK: We only need the public key for verification
     pub authority: UncheckedAccount<'info>,
 
-    pub mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
 
     #[account(
         mut,
         associated_token::mint = mint,
-        associated_token::authority = program_sale_authority
+        associated_token::authority = program_sale_authority,
+        associated_token::token_program = token_program,
     )]
-    pub program_token_account: Account<'info, TokenAccount>,
+    pub program_token_account: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         init_if_needed,
         payer = buyer,
         associated_token::mint = mint,
         associated_token::authority = buyer,
+        associated_token::token_program = token_program,
     )]
-    pub buyer_token_account: Account<'info, TokenAccount>,
+    pub buyer_token_account: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
         init_if_needed,
         payer = buyer,
         space = WALLET_PURCHASE_SIZE,
-        seeds = [b"wallet_purchase", buyer.key().as_ref()],
+        seeds = [b"wallet_purchase", sale_config.key().as_ref(), buyer.key().as_ref()],
         bump,
     )]
     pub wallet_purchase: Box<Account<'info, WalletPurchase>>,
 
-    #[account(mut)]
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = BUYER_VESTING_SIZE,
+        seeds = [BUYER_VESTING, sale_config.key().as_ref(), buyer.key().as_ref()],
+        bump,
+    )]
+    pub buyer_vesting: Option<Box<Account<'info, BuyerVesting>>>,
+
+    #[account(
+        mut,
+        seeds = [MONTHLY_LIMITS, sale_config.key().as_ref(), &[PoolKind::Sale as u8]],
+        bump,
+    )]
     pub monthly_limits: Box<Account<'info, MonthlyLimits>>,
 
+    #[account(
+        mut,
+        seeds = [MONTHLY_LIMITS, sale_config.key().as_ref(), &[PoolKind::Bonus as u8]],
+        bump,
+    )]
+    pub bonus_limits: Option<Box<Account<'info, MonthlyLimits>>>,
+
+    #[account(
+        mut,
+        seeds = [MONTHLY_LIMITS, sale_config.key().as_ref(), &[PoolKind::Referral as u8]],
+        bump,
+    )]
+    pub referral_limits: Option<Box<Account<'info, MonthlyLimits>>>,
+
+    #[account(
+        mut,
+        seeds = [PROCEEDS_VAULT, sale_config.key().as_ref()],
+        bump,
+    )]
+    pub proceeds_vault: Option<SystemAccount<'info>>,
+
+    #[account(mut)]
+    pub referrer: Option<SystemAccount<'info>>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = REFERRER_STATS_SIZE,
+        seeds = [REFERRER_STATS, sale_config.key().as_ref(), referrer.as_ref().map(|r| r.key()).unwrap_or_default().as_ref()],
+        bump,
+    )]
+    pub referrer_stats: Option<Box<Account<'info, ReferrerStats>>>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = mint,
+        associated_token::authority = referrer,
+        associated_token::token_program = token_program,
+    )]
+    pub referrer_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
     pub price_update: Box<Account<'info, PriceUpdateV2>>,
 
     pub system_program: Program<'info, System>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 
     pub associated_token_program: Program<'info, AssociatedToken>,
 }
 
 impl<'info> BuyTokens<'info> {
-    pub fn buy(&mut self, sol_amount: u64) -> Result<()> {
+    pub fn buy(&mut self, sol_amount: u64, recipient_accounts: &[AccountInfo<'info>], event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
 
         let current_timestamp = Clock::get()?.unix_timestamp;
 
         self.check_sale_paused()?;
+        self.check_sale_open(current_timestamp)?;
         self.initialize_wallet_purchase_if_needed()?;
 
-        let sol_price_usd = self.get_sol_price_usd()?;
+        let sol_price_usd = sol_price_usd(&self.price_update)?;
         let token_amount = self.calculate_token_amount(sol_amount, sol_price_usd)?;
+        let bonus_amount = self.calculate_bonus_amount(sol_amount, sol_price_usd, token_amount, current_timestamp, event_authority_bump)?;
+
+        let limited_amount = if self.sale_config.bonus.counts_toward_limits {
+            token_amount.checked_add(bonus_amount).ok_or(ErrorCode::MathOverflow)?
+        } else {
+            token_amount
+        };
 
-        self.check_wallet_purchase_limits(token_amount, current_timestamp)?;
-        self.check_monthly_limits(token_amount, current_timestamp)?;
+        self.check_wallet_purchase_limits(limited_amount, current_timestamp)?;
+        self.check_monthly_limits(limited_amount, current_timestamp, event_authority_bump)?;
 
-        self.transfer_sol(sol_amount)?;
-        self.transfer_tokens(token_amount)?;
+        let delivered_amount = token_amount.checked_add(bonus_amount).ok_or(ErrorCode::MathOverflow)?;
+        let split = split_purchase(&self.sale_config, &self.mint.to_account_info(), delivered_amount)?;
 
-        self.update_state(token_amount, current_timestamp)?;
+        self.draw_purchase(token_amount, bonus_amount, split.fee()?)?;
 
-        self.emit_purchase_event(sol_amount, token_amount, sol_price_usd)?;
+        let referral_sol = self.credit_referral(sol_amount, token_amount, current_timestamp, event_authority_bump)?;
+
+        self.transfer_sol(sol_amount - referral_sol, recipient_accounts)?;
+        self.transfer_tokens(self.buyer_token_account.to_account_info(), split.tokens_sent)?;
+
+        if !self.sale_config.mint_on_purchase {
+            self.sale_config.inventory.sold = self.sale_config.inventory.sold.checked_add(split.tokens_sent).ok_or(ErrorCode::MathOverflow)?;
+        }
+        self.lock_tokens(split.tokens_locked, split.claim_fee_reserve, event_authority_bump)?;
+
+        self.update_state(sol_amount, limited_amount, delivered_amount, current_timestamp)?;
+
+        self.emit_purchase_event(sol_amount, token_amount, bonus_amount, sol_price_usd, event_authority_bump)?;
 
         Ok(())
     }
@@ -105,51 +187,129 @@ impl<'info> BuyTokens<'info> {
         Ok(())
     }
 
+    /// Check the sale has not ended or been finalized
+    fn check_sale_open(&self, current_timestamp: i64) -> Result<()> {
+
+        require!(!self.sale_config.has_ended(current_timestamp), ErrorCode::SaleEnded);
+
+        Ok(())
+    }
+
     /// Initialize wallet purchase if needed
     fn initialize_wallet_purchase_if_needed(&mut self) -> Result<()> {
 
         if self.wallet_purchase.wallet == Pubkey::default() {
             self.wallet_purchase.wallet = self.buyer.key();
+            self.wallet_purchase.sale = self.sale_config.key();
             self.wallet_purchase.total_purchased = DEFAULT;
+            self.wallet_purchase.lifetime_purchased = DEFAULT;
+            self.wallet_purchase.total_spent = DEFAULT;
             self.wallet_purchase.last_purchased_timestamp = DEFAULT as i64;
         }
 
         Ok(())
     }
 
-    /// Get SOL/USD price from Pyth
-    fn get_sol_price_usd(&self) -> Result<f64> {
+    /// Calculate token amount based on SOL amount and price
+    fn calculate_token_amount(&self, sol_amount: u64, sol_price_usd: f64) -> Result<u64> {
 
-        let feed_id = get_feed_id_from_hex(SOL_USD_FEED_ID)?;
-        let price_data = self.price_update.get_price_no_older_than(
-            &Clock::get()?,
-            MAX_AGE,
-            &feed_id,
-        )?;
-        let sol_price_usd = (price_data.price as f64) * 10f64.powi(price_data.exponent);
+        let sol_amount_usd = purchase::purchase_value_usd(sol_amount, sol_price_usd);
+        let token_amount = purchase::token_amount(sol_amount_usd, self.sale_config.token_price_usd, self.sale_config.mint_decimals);
+
+        require!(self.sale_config.pool(PoolKind::Sale).remaining() >= token_amount, ErrorCode::InsufficientTokens);
 
-        Ok(sol_price_usd)
+        Ok(token_amount)
     }
 
-    /// Calculate token amount based on SOL amount and price
-    fn calculate_token_amount(&self, sol_amount: u64, sol_price_usd: f64) -> Result<u64> {
+    /// Calculate the early-bird and volume bonus, bounded by what the bonus pool can pay right now
+    fn calculate_bonus_amount(&mut self, sol_amount: u64, sol_price_usd: f64, token_amount: u64, current_timestamp: i64, event_authority_bump: u8) -> Result<u64> {
 
-        let token_price_usd = self.sale_config.token_price_usd;
-        let sol_amount_usd = sol_amount as f64 / 10_f64.powf(SOL_DECIMALS) * sol_price_usd;
-        let decimals = self.sale_config.mint_decimals;
-        let token_amount = (sol_amount_usd / token_price_usd * 10_f64.powf(decimals as f64)) as u64;
+        if !self.sale_config.bonus.enabled {
+            return Ok(0);
+        }
 
-        require!(self.program_token_account.amount >= token_amount, ErrorCode::InsufficientTokens);
+        let bonus_available = self.pool_allowance(PoolKind::Bonus, current_timestamp, event_authority_bump)?;
 
-        Ok(token_amount)
+        Ok(purchase::bonus_amount(
+            &self.sale_config.bonus,
+            token_amount,
+            purchase::purchase_value_usd(sol_amount, sol_price_usd),
+            current_timestamp,
+            bonus_available,
+        ))
+    }
+
+    /// Charge a purchase to its pools: the sale pool pays the purchased tokens and the transfer fees, the bonus pool the bonus
+    fn draw_purchase(&mut self, token_amount: u64, bonus_amount: u64, fee: u64) -> Result<()> {
+
+        let sale_amount = token_amount.checked_add(fee).ok_or(ErrorCode::MathOverflow)?;
+        budget::draw(self.sale_config.pool_mut(PoolKind::Sale), sale_amount)?;
+
+        if bonus_amount > 0 {
+            self.draw_pool(PoolKind::Bonus, bonus_amount)?;
+        }
+
+        Ok(())
+    }
+
+    /// What the bonus or referral pool can pay right now: its remaining budget,
+    /// capped by the pool's schedule when vesting is enabled on it
+    fn pool_allowance(&mut self, kind: PoolKind, current_timestamp: i64, event_authority_bump: u8) -> Result<u64> {
+
+        let remaining = self.sale_config.pool(kind).remaining();
+        let header = EventHeader::new(&self.sale_config, self.buyer.key())?;
+        let event_authority = self.event_authority.clone();
+
+        let Some(schedule) = self.pool_schedule(kind)? else {
+            return Ok(remaining);
+        };
+
+        if !schedule.is_vesting_enabled {
+            return Ok(remaining);
+        }
+
+        // Nothing is unlocked before the pool's first tranche, the purchase goes through without it
+        let unlock = match vesting::advance(schedule, current_timestamp, remaining) {
+            Err(ErrorCode::SaleNotStarted) => return Ok(0),
+            unlock => unlock?,
+        };
+        unlock.apply(schedule)?;
+
+        emit_unlock(&event_authority, event_authority_bump, header, &unlock, schedule)?;
+
+        Ok(remaining.min(unlock.tokens_available))
+    }
+
+    /// Record `token_amount` as paid by the bonus or referral pool and its schedule
+    fn draw_pool(&mut self, kind: PoolKind, token_amount: u64) -> Result<()> {
+
+        budget::draw(self.sale_config.pool_mut(kind), token_amount)?;
+
+        if let Some(schedule) = self.pool_schedule(kind)? {
+            vesting::release(schedule, token_amount)?;
+        }
+
+        Ok(())
+    }
+
+    /// Schedule of the bonus or referral pool, which has to be passed once the pool has one
+    fn pool_schedule(&mut self, kind: PoolKind) -> Result<Option<&mut Account<'info, MonthlyLimits>>> {
+
+        let schedule = match kind {
+            PoolKind::Bonus => self.bonus_limits.as_deref_mut(),
+            PoolKind::Referral => self.referral_limits.as_deref_mut(),
+            PoolKind::Sale | PoolKind::Treasury => None,
+        };
+
+        require!(schedule.is_some() || !self.sale_config.pool(kind).scheduled, ErrorCode::PoolScheduleMissing);
+
+        Ok(schedule)
     }
 
     /// Check wallet purchase limits
     fn check_wallet_purchase_limits(&mut self, token_amount: u64, current_timestamp: i64) -> Result<()> {
 
-        if current_timestamp - self.wallet_purchase.last_purchased_timestamp > SECONDS_IN_A_DAY {
-            self.wallet_purchase.total_purchased = DEFAULT;
-        }
+        self.wallet_purchase.total_purchased = purchase::wallet_window_purchased(&self.wallet_purchase, current_timestamp);
 
         require!(
             self.wallet_purchase.total_purchased + token_amount <= self.sale_config.wallet_purchase_limit,
@@ -160,117 +320,341 @@ impl<'info> BuyTokens<'info> {
     }
 
     /// Check monthly limits
-    fn check_monthly_limits(&mut self, token_amount: u64, current_timestamp: i64) -> Result<()> {
+    fn check_monthly_limits(&mut self, token_amount: u64, current_timestamp: i64, event_authority_bump: u8) -> Result<()> {
 
-       if !self.monthly_limits.is_vesting_enabled{
+        if !self.monthly_limits.is_vesting_enabled {
             return Ok(());
         }
 
-        let timestamps = self.monthly_limits.timestamps;
-        let limits = self.monthly_limits.limits;
+        let inventory = self.sale_config.pool(PoolKind::Sale).remaining();
+        let unlock = vesting::advance(&self.monthly_limits, current_timestamp, inventory)?;
+        unlock.apply(&mut self.monthly_limits)?;
 
-        if self.monthly_limits.last_checked_index  as usize >= timestamps.len() - 1{
-            self.monthly_limits.tokens_available = self.program_token_account.amount;
-            return Ok(());
-        } 
+        emit_unlock(&self.event_authority, event_authority_bump, EventHeader::new(&self.sale_config, self.buyer.key())?, &unlock, &self.monthly_limits)?;
 
-        if current_timestamp <= self.monthly_limits.timestamps[self.monthly_limits.last_checked_index  as usize + 1]{
+        unlock.check(token_amount)?;
 
-            if current_timestamp < self.monthly_limits.timestamps[DEFAULT as usize]{
-                return Err(ErrorCode::SaleNotStarted.into());
-            }
+        Ok(())
+    }
 
-            if self.monthly_limits.tokens_available == 0 && self.monthly_limits.tokens_unlocked == 0{
-                self.monthly_limits.tokens_available = limits[self.monthly_limits.last_checked_index as usize];
-            }
+    /// Transfer SOL from buyer to the recipient table, or to the proceeds vault when it is enabled.
+    /// `recipient_accounts` lists the table wallets in order, rounding dust goes to the primary recipient.
+    fn transfer_sol(&mut self, sol_amount: u64, recipient_accounts: &[AccountInfo<'info>]) -> Result<()> {
 
+        if self.sale_config.proceeds_vault_enabled {
+            let proceeds_vault = self.proceeds_vault.as_ref().ok_or(ErrorCode::ProceedsVaultMissing)?;
+            self.pay(proceeds_vault.to_account_info(), sol_amount)?;
+        } else {
+            let (shares, dust) = self.sale_config.split_proceeds(sol_amount);
 
-        require!(
-            token_amount <= self.monthly_limits.tokens_available,
-            ErrorCode::MonthlyLimitExceeded
-        );
+            require!(recipient_accounts.len() == shares.len(), ErrorCode::RecipientAccountsMismatch);
 
-            Ok(())
-        }
-
-        else{
-            let mut i = self.monthly_limits.last_checked_index as usize + 1;
-            let mut temp_var  = self.monthly_limits.last_checked_index as usize;
-
-            while i < timestamps.len() && current_timestamp >= timestamps[i]{
-                temp_var = i;
-                self.monthly_limits.tokens_available += limits[i];
-                i += 1;
+            for ((account, table_entry), share) in recipient_accounts.iter().zip(self.sale_config.recipient_shares()).zip(shares) {
+                require_keys_eq!(account.key(), table_entry.wallet, ErrorCode::RecipientAccountsMismatch);
+                if share > 0 {
+                    self.pay(account.clone(), share)?;
+                }
             }
-            self.monthly_limits.last_checked_index = temp_var as u8;
 
-        require!(
-            token_amount <= self.monthly_limits.tokens_available,
-            ErrorCode::MonthlyLimitExceeded
-        );
-            Ok(())
+            if dust > 0 {
+                self.pay(self.sale_authority.to_account_info(), dust)?;
+            }
         }
-   }
 
-    /// Transfer SOL from buyer to sale authority
-    fn transfer_sol(&self, sol_amount: u64) -> Result<()> {
+        self.sale_config.total_raised = self.sale_config.total_raised
+            .checked_add(sol_amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Transfer SOL from buyer to `to`
+    fn pay(&self, to: AccountInfo<'info>, lamports: u64) -> Result<()> {
 
         let cpi_context = CpiContext::new(
             self.system_program.to_account_info(),
             anchor_lang::system_program::Transfer {
                 from: self.buyer.to_account_info(),
-                to: self.sale_authority.to_account_info(),
+                to,
             },
         );
-        anchor_lang::system_program::transfer(cpi_context, sol_amount)?;
+        anchor_lang::system_program::transfer(cpi_context, lamports)?;
 
         Ok(())
     }
 
-    /// Transfer tokens from program to buyer
-    fn transfer_tokens(&self, token_amount: u64) -> Result<()> {
+    /// Transfer tokens from program to `to`, or mint them to `to` in mint-on-purchase mode
+    fn transfer_tokens(&self, to: AccountInfo<'info>, token_amount: u64) -> Result<()> {
 
+        let sale_key = self.sale_config.key();
         let authority_seeds: &[&[u8]] = &[
             SALE_AUTHORITY,
+            sale_key.as_ref(),
             &[self.sale_config.bump],
         ];
 
-        anchor_spl::token::transfer(
+        if self.sale_config.mint_on_purchase {
+            anchor_spl::token_interface::mint_to(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    anchor_spl::token_interface::MintTo {
+                        mint: self.mint.to_account_info(),
+                        to,
+                        authority: self.program_sale_authority.to_account_info(),
+                    },
+                    &[authority_seeds]
+                ),
+                token_amount,
+            )?;
+
+            return Ok(());
+        }
+
+        anchor_spl::token_interface::transfer_checked(
             CpiContext::new_with_signer(
                 self.token_program.to_account_info(),
-                anchor_spl::token::Transfer {
+                anchor_spl::token_interface::TransferChecked {
                     from: self.program_token_account.to_account_info(),
-                    to: self.buyer_token_account.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                    to,
                     authority: self.program_sale_authority.to_account_info(),
                 },
                 &[authority_seeds]
             ),
-            token_amount
+            token_amount,
+            self.mint.decimals,
         )?;
 
         Ok(())
     }
 
-    /// Update state after purchase
-    fn update_state(&mut self, token_amount: u64, current_timestamp: i64) -> Result<()> {
+    /// Credit the referrer of a purchase, returning the lamports of the payment owed to them as a SOL reward
+    fn credit_referral(&mut self, sol_amount: u64, token_amount: u64, current_timestamp: i64, event_authority_bump: u8) -> Result<u64> {
+
+        let config = self.sale_config.referral;
+
+        let Some(referrer) = self.referrer.as_ref() else {
+            return Ok(0);
+        };
+
+        if !config.enabled {
+            return Ok(0);
+        }
+
+        require_keys_neq!(referrer.key(), self.buyer.key(), ErrorCode::SelfReferral);
+
+        let referrer = referrer.to_account_info();
+        let referral_available = match config.reward_kind {
+            ReferralReward::Tokens => {
+                let allowance = self.pool_allowance(PoolKind::Referral, current_timestamp, event_authority_bump)?;
+                referral_reward_allowance(&self.sale_config, &self.mint.to_account_info(), allowance)?
+            }
+            ReferralReward::Sol => 0,
+        };
+        let stats = self.referrer_stats.as_mut().ok_or(ErrorCode::ReferralAccountsMissing)?;
+
+        if stats.referrer == Pubkey::default() {
+            stats.referrer = referrer.key();
+            stats.sale = self.sale_config.key();
+        }
+
+        let reward = purchase::referral_reward(&config, stats, sol_amount, token_amount, referral_available);
+
+        stats.referral_count = stats.referral_count.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+        stats.referred_sol = stats.referred_sol.checked_add(sol_amount).ok_or(ErrorCode::MathOverflow)?;
+        stats.referred_tokens = stats.referred_tokens.checked_add(token_amount).ok_or(ErrorCode::MathOverflow)?;
+
+        let total_rewards = match config.reward_kind {
+            ReferralReward::Tokens => {
+                stats.token_rewards = stats.token_rewards.checked_add(reward).ok_or(ErrorCode::MathOverflow)?;
+                stats.token_rewards
+            }
+            ReferralReward::Sol => {
+                stats.sol_rewards = stats.sol_rewards.checked_add(reward).ok_or(ErrorCode::MathOverflow)?;
+                stats.sol_rewards
+            }
+        };
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, ReferralCredited {
+            header: EventHeader::new(&self.sale_config, self.buyer.key())?,
+            referrer: referrer.key(),
+            buyer: self.buyer.key(),
+            sol_amount,
+            token_amount,
+            reward_kind: config.reward_kind,
+            reward,
+            total_rewards,
+            referred_sol: stats.referred_sol,
+            referred_tokens: stats.referred_tokens,
+        })?;
+
+        if reward == 0 {
+            return Ok(0);
+        }
+
+        match config.reward_kind {
+            ReferralReward::Tokens => {
+                let referrer_token_account = self.referrer_token_account
+                    .as_ref()
+                    .ok_or(ErrorCode::ReferralAccountsMissing)?
+                    .to_account_info();
+
+                // The referral pool pays the transfer fee, the referrer receives the whole reward
+                let tokens_sent = if self.sale_config.mint_on_purchase {
+                    reward
+                } else {
+                    transfer_fee::gross_up(&self.mint.to_account_info(), reward, Clock::get()?.epoch)?
+                };
+
+                self.draw_pool(PoolKind::Referral, tokens_sent)?;
+                self.transfer_tokens(referrer_token_account, tokens_sent)?;
+
+                if !self.sale_config.mint_on_purchase {
+                    self.sale_config.inventory.rewarded = self.sale_config.inventory.rewarded.checked_add(tokens_sent).ok_or(ErrorCode::MathOverflow)?;
+                }
+
+                Ok(0)
+            }
+            ReferralReward::Sol => {
+                self.pay(referrer, reward)?;
+
+                Ok(reward)
+            }
+        }
+    }
+
+    /// Credit the locked part of a purchase to the buyer's vesting position and set aside its claim fee
+    fn lock_tokens(&mut self, tokens_locked: u64, claim_fee_reserve: u64, event_authority_bump: u8) -> Result<()> {
+
+        if !self.sale_config.buyer_vesting.enabled {
+            return Ok(());
+        }
+
+        let buyer = self.buyer.key();
+        let position = self.buyer_vesting.as_mut().ok_or(ErrorCode::BuyerVestingAccountMissing)?;
+
+        vesting::check_position_sale(position, &self.sale_config.key())?;
+
+        if position.owner == Pubkey::default() {
+            position.owner = buyer;
+            position.sale = self.sale_config.key();
+        }
+
+        position.total_locked = position.total_locked.checked_add(tokens_locked).ok_or(ErrorCode::MathOverflow)?;
+        self.sale_config.tokens_locked = self.sale_config.tokens_locked.checked_add(tokens_locked).ok_or(ErrorCode::MathOverflow)?;
+        self.sale_config.claim_fee_reserve = self.sale_config.claim_fee_reserve.checked_add(claim_fee_reserve).ok_or(ErrorCode::MathOverflow)?;
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, TokensLocked {
+            header: EventHeader::new(&self.sale_config, self.buyer.key())?,
+            owner: position.owner,
+            tokens_locked,
+            total_locked: position.total_locked,
+        })?;
+
+        // Claims are paid from the program account, so minted sales mint the locked share and its claim fee there
+        let tokens_minted = tokens_locked.checked_add(claim_fee_reserve).ok_or(ErrorCode::MathOverflow)?;
+        if self.sale_config.mint_on_purchase && tokens_minted > 0 {
+            self.transfer_tokens(self.program_token_account.to_account_info(), tokens_minted)?;
+            self.sale_config.inventory.minted = self.sale_config.inventory.minted.checked_add(tokens_minted).ok_or(ErrorCode::MathOverflow)?;
+        }
+
+        Ok(())
+    }
+
+    /// Update state after purchase, `token_amount` counts toward the limits and `delivered_amount` is every token bought
+    fn update_state(&mut self, sol_amount: u64, token_amount: u64, delivered_amount: u64, current_timestamp: i64) -> Result<()> {
 
         self.wallet_purchase.total_purchased += token_amount;
+        self.wallet_purchase.lifetime_purchased = self.wallet_purchase.lifetime_purchased.checked_add(delivered_amount).ok_or(ErrorCode::MathOverflow)?;
+        self.wallet_purchase.total_spent = self.wallet_purchase.total_spent.checked_add(sol_amount).ok_or(ErrorCode::MathOverflow)?;
         self.wallet_purchase.last_purchased_timestamp = current_timestamp;
-        self.monthly_limits.tokens_unlocked += token_amount;
-        self.monthly_limits.tokens_available -= token_amount;
+
+        self.sale_config.tokens_sold = self.sale_config.tokens_sold.checked_add(delivered_amount).ok_or(ErrorCode::MathOverflow)?;
+
+        vesting::release(&mut self.monthly_limits, token_amount)?;
 
         Ok(())
     }
 
     /// Emit purchase event
-    fn emit_purchase_event(&self, sol_amount: u64, token_amount: u64, sol_price_usd: f64) -> Result<()> {
-        emit!(TokensPurchased {
+    fn emit_purchase_event(&self, sol_amount: u64, token_amount: u64, bonus_amount: u64, sol_price_usd: f64, event_authority_bump: u8) -> Result<()> {
+        emit_cpi_event(&self.event_authority, event_authority_bump, TokensPurchased {
+            header: EventHeader::new(&self.sale_config, self.buyer.key())?,
             buyer: self.buyer.key(),
             sol_amount,
             token_amount,
+            bonus_amount,
             sol_price: sol_price_usd,
-        });
+            total_sold: self.sale_config.tokens_sold,
+            total_raised: self.sale_config.total_raised,
+            buyer_window_purchased: self.wallet_purchase.total_purchased,
+            buyer_lifetime_purchased: self.wallet_purchase.lifetime_purchased,
+            buyer_total_spent: self.wallet_purchase.total_spent,
+        })?;
 
         Ok(())
     }
+
+}
+
+/// How a purchase delivering `token_amount` plus bonus leaves the sale
+pub(crate) struct PurchaseSplit {
+    pub tokens_released: u64,       // Credited to the buyer now
+    pub tokens_sent: u64,           // Sent to the buyer, the released share grossed up by the Token-2022 transfer fee
+    pub tokens_locked: u64,         // Credited to the buyer's vesting position
+    pub claim_fee_reserve: u64,     // Transfer fee the locked share will cost when it is claimed
+}
+
+impl PurchaseSplit {
+    /// Transfer fees the sale pool pays on top of the purchased tokens
+    pub fn fee(&self) -> Result<u64> {
+        Ok((self.tokens_sent - self.tokens_released).checked_add(self.claim_fee_reserve).ok_or(ErrorCode::MathOverflow)?)
+    }
+}
+
+/// Split `delivered_amount` into its released and locked shares and work out the transfer fees of each.
+/// Limits only count what the buyer receives. Minted tokens carry no fee, but claims of the locked share
+/// are paid from the program account in either mode.
+pub(crate) fn split_purchase(sale_config: &SaleConfig, mint: &AccountInfo, delivered_amount: u64) -> Result<PurchaseSplit> {
+
+    let epoch = Clock::get()?.epoch;
+    let (tokens_released, tokens_locked) = vesting::split_purchase(&sale_config.buyer_vesting, delivered_amount);
+
+    let tokens_sent = if sale_config.mint_on_purchase {
+        tokens_released
+    } else {
+        transfer_fee::gross_up(mint, tokens_released, epoch)?
+    };
+
+    Ok(PurchaseSplit {
+        tokens_released,
+        tokens_sent,
+        tokens_locked,
+        claim_fee_reserve: transfer_fee::gross_up(mint, tokens_locked, epoch)? - tokens_locked,
+    })
+}
+
+/// Largest token reward the referral pool can pay out of `allowance` with the Token-2022 transfer fee on top.
+/// Minted rewards carry no fee.
+pub(crate) fn referral_reward_allowance(sale_config: &SaleConfig, mint: &AccountInfo, allowance: u64) -> Result<u64> {
+
+    if sale_config.mint_on_purchase {
+        return Ok(allowance);
+    }
+
+    Ok(allowance - transfer_fee::fee(mint, allowance, Clock::get()?.epoch)?)
+}
+
+/// Get SOL/USD price from Pyth
+pub(crate) fn sol_price_usd(price_update: &PriceUpdateV2) -> Result<f64> {
+
+    let feed_id = get_feed_id_from_hex(SOL_USD_FEED_ID)?;
+    let price_data = price_update.get_price_no_older_than(
+        &Clock::get()?,
+        MAX_AGE,
+        &feed_id,
+    )?;
+    let sol_price_usd = (price_data.price as f64) * 10f64.powi(price_data.exponent);
+
+    Ok(sol_price_usd)
 }
@@ -0,0 +1,121 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenInterface, TokenAccount},
+};
+use crate::{states::*, events::*, constants::*, error::ErrorCode, transfer_fee, vesting};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Claim<'info> {
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [BUYER_VESTING, sale_config.key().as_ref(), owner.key().as_ref()],
+        bump,
+        has_one = owner,
+        constraint = buyer_vesting.sale == sale_config.key() @ ErrorCode::WrongSale,
+    )]
+    pub buyer_vesting: Box<Account<'info, BuyerVesting>>,
+
+    #[account(
+        mut,
+        constraint = sale_config.token_mint == mint.key() @ ErrorCode::InvalidTokenMint
+    )]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [SALE_AUTHORITY, sale_config.key().as_ref()],
+        bump = sale_config.bump,
+    )]
+    pub program_sale_authority: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = program_sale_authority,
+        associated_token::token_program = token_program,
+    )]
+    pub program_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        associated_token::mint = mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program,
+    )]
+    pub owner_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub system_program: Program<'info, System>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> Claim<'info> {
+    pub fn claim(&mut self, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
+
+        let current_timestamp = Clock::get()?.unix_timestamp;
+
+        let claimable = vesting::claimable(&self.sale_config.buyer_vesting, &self.buyer_vesting, current_timestamp);
+
+        require!(claimable > 0, ErrorCode::NothingToClaim);
+
+        // Gross up by the Token-2022 transfer fee so the owner receives `claimable`
+        let tokens_sent = transfer_fee::gross_up(&self.mint.to_account_info(), claimable, Clock::get()?.epoch)?;
+
+        self.transfer_tokens(tokens_sent)?;
+
+        self.buyer_vesting.claimed += claimable;
+        self.sale_config.settle_claim(claimable, tokens_sent)?;
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, TokensClaimed {
+            header: EventHeader::new(&self.sale_config, self.owner.key())?,
+            owner: self.owner.key(),
+            claimed_by: self.owner.key(),
+            tokens_claimed: claimable,
+            cranker_reward: 0,
+            total_claimed: self.buyer_vesting.claimed,
+            remaining: self.buyer_vesting.total_locked - self.buyer_vesting.claimed,
+        })?;
+
+        Ok(())
+    }
+
+    /// Transfer vested tokens from program to owner
+    fn transfer_tokens(&self, token_amount: u64) -> Result<()> {
+
+        let sale_key = self.sale_config.key();
+        let authority_seeds: &[&[u8]] = &[
+            SALE_AUTHORITY,
+            sale_key.as_ref(),
+            &[self.sale_config.bump],
+        ];
+
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: self.program_token_account.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                    to: self.owner_token_account.to_account_info(),
+                    authority: self.program_sale_authority.to_account_info(),
+                },
+                &[authority_seeds]
+            ),
+            token_amount,
+            self.mint.decimals,
+        )?;
+
+        Ok(())
+    }
+}
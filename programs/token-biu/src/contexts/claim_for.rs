@@ -0,0 +1,283 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenInterface, TokenAccount},
+};
+use crate::{states::*, events::*, constants::*, error::ErrorCode, transfer_fee, vesting};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimFor<'info> {
+
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    /// CHECK: Owner of the position, only used as the token account authority
+    #[account(address = buyer_vesting.owner)]
+    pub owner: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = buyer_vesting.sale == sale_config.key() @ ErrorCode::WrongSale,
+    )]
+    pub buyer_vesting: Box<Account<'info, BuyerVesting>>,
+
+    #[account(
+        mut,
+        constraint = sale_config.token_mint == mint.key() @ ErrorCode::InvalidTokenMint
+    )]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        seeds = [SALE_AUTHORITY, sale_config.key().as_ref()],
+        bump = sale_config.bump,
+    )]
+    pub program_sale_authority: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = program_sale_authority,
+        associated_token::token_program = token_program,
+    )]
+    pub program_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = cranker,
+        associated_token::mint = mint,
+        associated_token::authority = owner,
+        associated_token::token_program = token_program,
+    )]
+    pub owner_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = cranker,
+        token::token_program = token_program,
+    )]
+    pub cranker_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub system_program: Program<'info, System>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> ClaimFor<'info> {
+    /// Release a position's vested tokens to its owner on their behalf
+    pub fn claim_for(&mut self, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
+
+        let current_timestamp = Clock::get()?.unix_timestamp;
+
+        let claimable = vesting::claimable(&self.sale_config.buyer_vesting, &self.buyer_vesting, current_timestamp);
+
+        require!(claimable > 0, ErrorCode::NothingToClaim);
+
+        let cranker_reward = match self.cranker_token_account {
+            Some(_) => vesting::cranker_reward(claimable, self.sale_config.claim_reward_bps, self.buyer_vesting.accepted_reward_bps),
+            None => 0,
+        };
+
+        // Gross up each transfer by the Token-2022 transfer fee so the owner and the cranker receive their share
+        let epoch = Clock::get()?.epoch;
+        let mut tokens_sent = transfer_fee::gross_up(&self.mint.to_account_info(), claimable - cranker_reward, epoch)?;
+        self.transfer_tokens(self.owner_token_account.to_account_info(), tokens_sent)?;
+
+        if let Some(cranker_token_account) = &self.cranker_token_account {
+            if cranker_reward > 0 {
+                let reward_sent = transfer_fee::gross_up(&self.mint.to_account_info(), cranker_reward, epoch)?;
+                self.transfer_tokens(cranker_token_account.to_account_info(), reward_sent)?;
+                tokens_sent = tokens_sent.checked_add(reward_sent).ok_or(ErrorCode::MathOverflow)?;
+            }
+        }
+
+        self.buyer_vesting.claimed += claimable;
+        self.sale_config.settle_claim(claimable, tokens_sent)?;
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, TokensClaimed {
+            header: EventHeader::new(&self.sale_config, self.cranker.key())?,
+            owner: self.buyer_vesting.owner,
+            claimed_by: self.cranker.key(),
+            tokens_claimed: claimable - cranker_reward,
+            cranker_reward,
+            total_claimed: self.buyer_vesting.claimed,
+            remaining: self.buyer_vesting.total_locked - self.buyer_vesting.claimed,
+        })?;
+
+        Ok(())
+    }
+
+    /// Transfer tokens from program to `to`
+    fn transfer_tokens(&self, to: AccountInfo<'info>, token_amount: u64) -> Result<()> {
+
+        let sale_key = self.sale_config.key();
+        let authority_seeds: &[&[u8]] = &[
+            SALE_AUTHORITY,
+            sale_key.as_ref(),
+            &[self.sale_config.bump],
+        ];
+
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: self.program_token_account.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                    to,
+                    authority: self.program_sale_authority.to_account_info(),
+                },
+                &[authority_seeds]
+            ),
+            token_amount,
+            self.mint.decimals,
+        )?;
+
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimForBatch<'info> {
+
+    pub cranker: Signer<'info>,
+
+    #[account(
+        mut,
+        constraint = sale_config.token_mint == mint.key() @ ErrorCode::InvalidTokenMint
+    )]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        seeds = [SALE_AUTHORITY, sale_config.key().as_ref()],
+        bump = sale_config.bump,
+    )]
+    pub program_sale_authority: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = program_sale_authority,
+        associated_token::token_program = token_program,
+    )]
+    pub program_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = cranker,
+        token::token_program = token_program,
+    )]
+    pub cranker_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> ClaimForBatch<'info> {
+    /// Release vested tokens for every (position, owner token account) pair in `remaining_accounts`.
+    /// Owner token accounts must already exist, positions with nothing vested are skipped.
+    pub fn claim_for_batch(&mut self, remaining_accounts: &'info [AccountInfo<'info>], event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
+
+        require!(
+            !remaining_accounts.is_empty() && remaining_accounts.chunks_exact(2).remainder().is_empty(),
+            ErrorCode::InvalidRemainingAccounts
+        );
+
+        let clock = Clock::get()?;
+        let current_timestamp = clock.unix_timestamp;
+        let mut total_reward = 0u64;
+
+        for pair in remaining_accounts.chunks(2) {
+
+            require!(pair[0].is_writable && pair[1].is_writable, ErrorCode::InvalidRemainingAccounts);
+
+            let mut position = Account::<BuyerVesting>::try_from(&pair[0])?;
+            let owner_token_account = InterfaceAccount::<TokenAccount>::try_from(&pair[1])?;
+
+            require_keys_eq!(position.sale, self.sale_config.key(), ErrorCode::WrongSale);
+            require_keys_eq!(owner_token_account.mint, self.mint.key(), ErrorCode::InvalidTokenMint);
+            require_keys_eq!(owner_token_account.owner, position.owner, ErrorCode::InvalidRemainingAccounts);
+
+            let claimable = vesting::claimable(&self.sale_config.buyer_vesting, &position, current_timestamp);
+
+            if claimable == 0 {
+                continue;
+            }
+
+            let cranker_reward = match self.cranker_token_account {
+                Some(_) => vesting::cranker_reward(claimable, self.sale_config.claim_reward_bps, position.accepted_reward_bps),
+                None => 0,
+            };
+
+            // Gross up by the Token-2022 transfer fee so the owner receives their share
+            let tokens_sent = transfer_fee::gross_up(&self.mint.to_account_info(), claimable - cranker_reward, clock.epoch)?;
+            self.transfer_tokens(pair[1].clone(), tokens_sent)?;
+
+            position.claimed += claimable;
+            position.exit(&crate::ID)?;
+
+            // The reward is settled with the single transfer to the cranker below
+            self.sale_config.settle_claim(claimable - cranker_reward, tokens_sent)?;
+            total_reward += cranker_reward;
+
+            emit_cpi_event(&self.event_authority, event_authority_bump, TokensClaimed {
+                header: EventHeader::new(&self.sale_config, self.cranker.key())?,
+                owner: position.owner,
+                claimed_by: self.cranker.key(),
+                tokens_claimed: claimable - cranker_reward,
+                cranker_reward,
+                total_claimed: position.claimed,
+                remaining: position.total_locked - position.claimed,
+            })?;
+        }
+
+        if let Some(cranker_token_account) = &self.cranker_token_account {
+            if total_reward > 0 {
+                let reward_sent = transfer_fee::gross_up(&self.mint.to_account_info(), total_reward, clock.epoch)?;
+                self.transfer_tokens(cranker_token_account.to_account_info(), reward_sent)?;
+                self.sale_config.settle_claim(total_reward, reward_sent)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Transfer tokens from program to `to`
+    fn transfer_tokens(&self, to: AccountInfo<'info>, token_amount: u64) -> Result<()> {
+
+        let sale_key = self.sale_config.key();
+        let authority_seeds: &[&[u8]] = &[
+            SALE_AUTHORITY,
+            sale_key.as_ref(),
+            &[self.sale_config.bump],
+        ];
+
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: self.program_token_account.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                    to,
+                    authority: self.program_sale_authority.to_account_info(),
+                },
+                &[authority_seeds]
+            ),
+            token_amount,
+            self.mint.decimals,
+        )?;
+
+        Ok(())
+    }
+}
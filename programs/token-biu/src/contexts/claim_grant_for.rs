@@ -0,0 +1,201 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenInterface, TokenAccount},
+};
+use crate::{states::*, events::*, constants::*, error::ErrorCode, transfer_fee, vesting, budget, grants};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ClaimGrantFor<'info> {
+
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+
+    /// CHECK: Grant beneficiary, only used as the token account authority
+    #[account(address = grant.beneficiary)]
+    pub beneficiary: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        seeds = [GRANT, sale_config.key().as_ref(), grant.beneficiary.as_ref()],
+        bump,
+        constraint = grant.sale == sale_config.key() @ ErrorCode::WrongSale,
+    )]
+    pub grant: Box<Account<'info, Grant>>,
+
+    #[account(
+        mut,
+        seeds = [GRANT_VAULT, grant.key().as_ref()],
+        bump,
+    )]
+    pub grant_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        constraint = sale_config.token_mint == mint.key() @ ErrorCode::InvalidTokenMint
+    )]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    #[account(
+        seeds = [SALE_AUTHORITY, sale_config.key().as_ref()],
+        bump = sale_config.bump,
+    )]
+    pub program_sale_authority: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = program_sale_authority,
+        associated_token::token_program = token_program,
+    )]
+    pub program_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(mut)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = cranker,
+        associated_token::mint = mint,
+        associated_token::authority = beneficiary,
+        associated_token::token_program = token_program,
+    )]
+    pub beneficiary_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = cranker,
+        token::token_program = token_program,
+    )]
+    pub cranker_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub system_program: Program<'info, System>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> ClaimGrantFor<'info> {
+    /// Release a grant's vested tokens to its beneficiary on their behalf
+    pub fn claim_for(&mut self, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
+
+        let current_timestamp = Clock::get()?.unix_timestamp;
+
+        let vested = grants::vested_amount(&self.grant, current_timestamp);
+        let claimable = vested.saturating_sub(self.grant.claimed);
+
+        require!(claimable > 0, ErrorCode::NothingToClaim);
+
+        let cranker_reward = match self.cranker_token_account {
+            Some(_) => vesting::cranker_reward(claimable, self.sale_config.claim_reward_bps, self.grant.accepted_reward_bps),
+            None => 0,
+        };
+
+        self.pay(self.beneficiary_token_account.to_account_info(), claimable - cranker_reward)?;
+
+        if let Some(cranker_token_account) = self.cranker_token_account.as_ref().map(|account| account.to_account_info()) {
+            if cranker_reward > 0 {
+                self.pay(cranker_token_account, cranker_reward)?;
+            }
+        }
+
+        self.grant.claimed += claimable;
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, GrantClaimed {
+            header: EventHeader::new(&self.sale_config, self.cranker.key())?,
+            beneficiary: self.grant.beneficiary,
+            claimed_by: self.cranker.key(),
+            tokens_claimed: claimable - cranker_reward,
+            cranker_reward,
+            total_claimed: self.grant.claimed,
+            remaining: self.grant.total_amount - self.grant.claimed,
+        })?;
+
+        Ok(())
+    }
+
+    /// Send `token_amount` from the grant vault to `to`. The Token-2022 transfer fee is made up from the treasury pool,
+    /// sent from the program account or minted in mint-on-purchase mode, so `to` receives the full amount.
+    fn pay(&mut self, to: AccountInfo<'info>, token_amount: u64) -> Result<()> {
+
+        self.transfer_tokens(self.grant_vault.to_account_info(), to.clone(), token_amount)?;
+
+        let epoch = Clock::get()?.epoch;
+        let shortfall = transfer_fee::fee(&self.mint.to_account_info(), token_amount, epoch)?;
+
+        if shortfall == 0 {
+            return Ok(());
+        }
+
+        if self.sale_config.mint_on_purchase {
+            budget::draw(self.sale_config.pool_mut(PoolKind::Treasury), shortfall)?;
+            return self.mint_tokens(to, shortfall);
+        }
+
+        let top_up = transfer_fee::gross_up(&self.mint.to_account_info(), shortfall, epoch)?;
+        budget::draw(self.sale_config.pool_mut(PoolKind::Treasury), top_up)?;
+        self.transfer_tokens(self.program_token_account.to_account_info(), to, top_up)?;
+        self.sale_config.inventory.claimed = self.sale_config.inventory.claimed.checked_add(top_up).ok_or(ErrorCode::MathOverflow)?;
+
+        Ok(())
+    }
+
+    /// Transfer tokens from the grant vault or the program account to `to`
+    fn transfer_tokens(&self, from: AccountInfo<'info>, to: AccountInfo<'info>, token_amount: u64) -> Result<()> {
+
+        let sale_key = self.sale_config.key();
+        let authority_seeds: &[&[u8]] = &[
+            SALE_AUTHORITY,
+            sale_key.as_ref(),
+            &[self.sale_config.bump],
+        ];
+
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                anchor_spl::token_interface::TransferChecked {
+                    from,
+                    mint: self.mint.to_account_info(),
+                    to,
+                    authority: self.program_sale_authority.to_account_info(),
+                },
+                &[authority_seeds]
+            ),
+            token_amount,
+            self.mint.decimals,
+        )?;
+
+        Ok(())
+    }
+
+    /// Mint tokens to `to` with the sale authority
+    fn mint_tokens(&self, to: AccountInfo<'info>, token_amount: u64) -> Result<()> {
+
+        let sale_key = self.sale_config.key();
+        let authority_seeds: &[&[u8]] = &[
+            SALE_AUTHORITY,
+            sale_key.as_ref(),
+            &[self.sale_config.bump],
+        ];
+
+        anchor_spl::token_interface::mint_to(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                anchor_spl::token_interface::MintTo {
+                    mint: self.mint.to_account_info(),
+                    to,
+                    authority: self.program_sale_authority.to_account_info(),
+                },
+                &[authority_seeds]
+            ),
+            token_amount,
+        )?;
+
+        Ok(())
+    }
+}
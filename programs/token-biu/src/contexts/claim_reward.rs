@@ -0,0 +1,86 @@
+use anchor_lang::prelude::*;
+use crate::{states::*, events::*, constants::*, error::ErrorCode};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetPositionClaimReward<'info> {
+
+    pub owner: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [BUYER_VESTING, sale_config.key().as_ref(), owner.key().as_ref()],
+        bump,
+        has_one = owner,
+        constraint = buyer_vesting.sale == sale_config.key() @ ErrorCode::WrongSale,
+    )]
+    pub buyer_vesting: Box<Account<'info, BuyerVesting>>,
+
+    #[account(mut)]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+}
+
+impl<'info> SetPositionClaimReward<'info> {
+    /// Set the most a cranker may take from claims made for the owner, 0 opts out
+    pub fn set_accepted_reward(&mut self, accepted_reward_bps: u16, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
+
+        require!(accepted_reward_bps <= MAX_CLAIM_REWARD_BPS, ErrorCode::InvalidClaimReward);
+
+        let old_accepted_reward_bps = self.buyer_vesting.accepted_reward_bps;
+        self.buyer_vesting.accepted_reward_bps = accepted_reward_bps;
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, ClaimRewardAccepted {
+            header: EventHeader::new(&self.sale_config, self.owner.key())?,
+            owner: self.owner.key(),
+            account: self.buyer_vesting.key(),
+            old_accepted_reward_bps,
+            new_accepted_reward_bps: accepted_reward_bps,
+        })?;
+
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetGrantClaimReward<'info> {
+
+    pub beneficiary: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GRANT, sale_config.key().as_ref(), beneficiary.key().as_ref()],
+        bump,
+        has_one = beneficiary,
+        constraint = grant.sale == sale_config.key() @ ErrorCode::WrongSale,
+    )]
+    pub grant: Box<Account<'info, Grant>>,
+
+    #[account(mut)]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+}
+
+impl<'info> SetGrantClaimReward<'info> {
+    /// Set the most a cranker may take from claims made for the beneficiary, 0 opts out
+    pub fn set_accepted_reward(&mut self, accepted_reward_bps: u16, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
+
+        require!(accepted_reward_bps <= MAX_CLAIM_REWARD_BPS, ErrorCode::InvalidClaimReward);
+
+        let old_accepted_reward_bps = self.grant.accepted_reward_bps;
+        self.grant.accepted_reward_bps = accepted_reward_bps;
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, ClaimRewardAccepted {
+            header: EventHeader::new(&self.sale_config, self.beneficiary.key())?,
+            owner: self.beneficiary.key(),
+            account: self.grant.key(),
+            old_accepted_reward_bps,
+            new_accepted_reward_bps: accepted_reward_bps,
+        })?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,136 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenInterface, TokenAccount};
+use crate::{states::*, events::*, constants::*, error::ErrorCode, transfer_fee};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CreateGrant<'info> {
+
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = sale_config.token_mint == mint.key() @ ErrorCode::InvalidTokenMint
+    )]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    /// CHECK: Any wallet can receive a grant
+    pub beneficiary: UncheckedAccount<'info>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = GRANT_SIZE,
+        seeds = [GRANT, sale_config.key().as_ref(), beneficiary.key().as_ref()],
+        bump,
+    )]
+    pub grant: Box<Account<'info, Grant>>,
+
+    #[account(
+        init,
+        payer = authority,
+        seeds = [GRANT_VAULT, grant.key().as_ref()],
+        bump,
+        token::mint = mint,
+        token::authority = program_sale_authority,
+        token::token_program = token_program,
+    )]
+    pub grant_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [SALE_AUTHORITY, sale_config.key().as_ref()],
+        bump = sale_config.bump,
+    )]
+    pub program_sale_authority: SystemAccount<'info>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = authority,
+        token::token_program = token_program,
+    )]
+    pub authority_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub system_program: Program<'info, System>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> CreateGrant<'info> {
+    /// Create a grant on the standard schedule from `start_timestamp`: monthly unlocks over the first year, then a bulk unlock.
+    /// Amounts are in whole tokens and default to `MONTHLY_UNLOCK` and `BULK_UNLOCK`.
+    pub fn create(&mut self, start_timestamp: i64, monthly_unlock: Option<u64>, bulk_unlock: Option<u64>, revocable: bool, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
+
+        // A start in the past would hand out unlocks the beneficiary never waited for
+        require!(start_timestamp >= Clock::get()?.unix_timestamp, ErrorCode::InvalidTimestamp);
+
+        let monthly_end = start_timestamp.checked_add(GRANT_MONTHLY_SPAN).ok_or(ErrorCode::MathOverflow)?;
+        let bulk_timestamp = start_timestamp.checked_add(GRANT_BULK_DELAY).ok_or(ErrorCode::MathOverflow)?;
+
+        let unit = 10u64.checked_pow(self.mint.decimals as u32).ok_or(ErrorCode::MathOverflow)?;
+        let monthly_amount = monthly_unlock.unwrap_or(MONTHLY_UNLOCK).checked_mul(unit).ok_or(ErrorCode::MathOverflow)?;
+        let bulk_amount = bulk_unlock.unwrap_or(BULK_UNLOCK).checked_mul(unit).ok_or(ErrorCode::MathOverflow)?;
+
+        let total_amount = monthly_amount
+            .checked_mul(MONTHS_IN_A_YEAR as u64)
+            .and_then(|monthly_total| monthly_total.checked_add(bulk_amount))
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        let grant = &mut self.grant;
+
+        grant.beneficiary = self.beneficiary.key();
+        grant.sale = self.sale_config.key();
+        grant.total_amount = total_amount;
+        grant.monthly_amount = monthly_amount;
+        grant.monthly_start = start_timestamp;
+        grant.monthly_end = monthly_end;
+        grant.monthly_count = MONTHS_IN_A_YEAR;
+        grant.bulk_amount = bulk_amount;
+        grant.bulk_timestamp = bulk_timestamp;
+        grant.claimed = DEFAULT;
+        grant.revocable = revocable;
+        grant.revoked = false;
+        grant.accepted_reward_bps = DEFAULT as u16;
+
+        // The admin pays the Token-2022 transfer fee so the vault holds the whole grant
+        self.fund_vault(transfer_fee::gross_up(&self.mint.to_account_info(), total_amount, Clock::get()?.epoch)?)?;
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, GrantCreated {
+            header: EventHeader::new(&self.sale_config, self.authority.key())?,
+            beneficiary: self.grant.beneficiary,
+            start_timestamp,
+            total_amount,
+            monthly_amount,
+            bulk_amount,
+            revocable,
+        })?;
+
+        Ok(())
+    }
+
+    /// Transfer the full grant from the admin into the grant vault
+    fn fund_vault(&self, token_amount: u64) -> Result<()> {
+
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: self.authority_token_account.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                    to: self.grant_vault.to_account_info(),
+                    authority: self.authority.to_account_info(),
+                },
+            ),
+            token_amount,
+            self.mint.decimals,
+        )?;
+
+        Ok(())
+    }
+}
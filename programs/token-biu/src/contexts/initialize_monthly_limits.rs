@@ -0,0 +1,52 @@
+use anchor_lang::prelude::*;
+use crate::{states::*, events::*, constants::*};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(pool: PoolKind)]
+pub struct InitializeMonthlyLimits<'info> {
+
+    #[account(mut, has_one = authority)]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = MonthlyLimits::space(DEFAULT as usize),
+        seeds = [MONTHLY_LIMITS, sale_config.key().as_ref(), &[pool as u8]],
+        bump,
+    )]
+    pub monthly_limits: Box<Account<'info, MonthlyLimits>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> InitializeMonthlyLimits<'info> {
+    pub fn initialize(&mut self, pool: PoolKind, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
+
+        // The schedule starts empty and disabled, `set_monthly_limits` fills it in
+        self.monthly_limits.sale = self.sale_config.key();
+        self.monthly_limits.pool = pool;
+        self.monthly_limits.timestamps = Vec::new();
+        self.monthly_limits.limits = Vec::new();
+        self.monthly_limits.is_vesting_enabled = false;
+        self.monthly_limits.period = Period::Monthly;
+        self.monthly_limits.rollover_policy = RolloverPolicy::CarryOver;
+
+        // Draws from the pool have to pass its schedule from now on
+        self.sale_config.pool_mut(pool).scheduled = true;
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, ScheduleInitialized {
+            header: EventHeader::new(&self.sale_config, self.authority.key())?,
+            pool,
+        })?;
+
+        Ok(())
+    }
+}
+
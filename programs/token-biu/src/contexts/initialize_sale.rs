@@ -1,10 +1,11 @@
 use anchor_lang::prelude::*;
 use anchor_spl::{
-    
-    token::{Mint},
+    associated_token::get_associated_token_address_with_program_id,
+    token_interface::{Mint, TokenAccount},
 };
-use crate::{constants::*, events::*, states::*};
+use crate::{constants::*, events::*, states::*, error::ErrorCode, transfer_fee, proceeds};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct InitializeSale<'info> {
 
@@ -21,16 +22,28 @@ pub struct InitializeSale<'info> {
     #[account(mut)]
     pub recipient: SystemAccount<'info>,
 
-    pub token_mint: Account<'info, Mint>,
+    pub token_mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: Optional, when passed it must be the funded sale authority ATA, validated in `check_program_token_account`
+    pub program_token_account: Option<UncheckedAccount<'info>>,
 
     pub system_program: Program<'info, System>,
 
 }
 
 impl<'info> InitializeSale<'info> {
-    pub fn initialize(&mut self, token_price_usd: f64, mint_decimals: u64, purchase_limit: u64) -> Result<()> {
+    pub fn initialize(&mut self, token_price_usd: f64, purchase_limit: u64, event_authority_bump: u8) -> Result<()> {
+
+        let (sale_authority, bump) = SaleConfig::find_sale_authority(&self.sale_config.key());
 
-        let (sale_authority, bump) = Pubkey::find_program_address(&[SALE_AUTHORITY], &crate::ID);
+        self.validate_config(token_price_usd, purchase_limit, sale_authority)?;
+        require!(
+            !transfer_fee::has_transfer_hook(&self.token_mint.to_account_info())?,
+            ErrorCode::TransferHookNotSupported
+        );
+        let funded = self.check_program_token_account(sale_authority)?;
+
+        let mint_decimals = self.token_mint.decimals as u64;
         let sale_config = &mut self.sale_config;
 
         sale_config.authority = self.authority.key();
@@ -42,13 +55,50 @@ impl<'info> InitializeSale<'info> {
         sale_config.token_mint = self.token_mint.key();
         sale_config.bump = bump;
         sale_config.wallet_purchase_limit = purchase_limit;
+        sale_config.inventory.deposited = funded;
+        sale_config.advance_event_seq()?;
 
-        emit!(SaleInitialized {
+        emit_cpi_event(&self.event_authority, event_authority_bump, SaleInitialized {
+            header: EventHeader::new(sale_config, self.authority.key())?,
             authority: sale_config.authority,
             token_price: token_price_usd,
             recipient: sale_config.recipient,
-        });
+        })?;
+
+        Ok(())
+    }
+
+    /// Check the price, the wallet limit and the recipient
+    fn validate_config(&self, token_price_usd: f64, purchase_limit: u64, sale_authority: Pubkey) -> Result<()> {
+
+        require!(token_price_usd.is_finite() && token_price_usd > 0.0, ErrorCode::InvalidTokenPrice);
+        require!(purchase_limit > 0, ErrorCode::InvalidPurchaseLimit);
+
+        proceeds::validate_recipient(&self.recipient.key(), &sale_authority)?;
 
         Ok(())
     }
+
+    /// When passed, check that the sale authority ATA exists for this mint and holds tokens.
+    /// The account belongs to this sale alone, so its balance is the opening inventory.
+    fn check_program_token_account(&self, sale_authority: Pubkey) -> Result<u64> {
+
+        let Some(program_token_account) = self.program_token_account.as_ref() else {
+            return Ok(DEFAULT);
+        };
+
+        let token_program = self.token_mint.to_account_info().owner;
+        let expected = get_associated_token_address_with_program_id(&sale_authority, &self.token_mint.key(), token_program);
+
+        require_keys_eq!(program_token_account.key(), expected, ErrorCode::ProgramTokenAccountMismatch);
+        require!(
+            program_token_account.owner == token_program && !program_token_account.data_is_empty(),
+            ErrorCode::ProgramTokenAccountMissing
+        );
+
+        let token_account = TokenAccount::try_deserialize(&mut &program_token_account.try_borrow_data()?[..])?;
+        require!(token_account.amount > 0, ErrorCode::ProgramTokenAccountUnfunded);
+
+        Ok(token_account.amount)
+    }
 }
@@ -0,0 +1,148 @@
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+use crate::{states::*, events::*, constants::*, error::ErrorCode};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DepositInventory<'info> {
+
+    #[account(
+        mut,
+        constraint = sale_config.token_mint == mint.key() @ ErrorCode::InvalidTokenMint
+    )]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    pub depositor: Signer<'info>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [SALE_AUTHORITY, sale_config.key().as_ref()],
+        bump = sale_config.bump,
+    )]
+    pub program_sale_authority: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = program_sale_authority,
+        associated_token::token_program = token_program,
+    )]
+    pub program_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = depositor,
+        token::token_program = token_program,
+    )]
+    pub depositor_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+}
+
+impl<'info> DepositInventory<'info> {
+    /// Move tokens into the sale and record what the program account actually received
+    pub fn deposit(&mut self, amount: u64, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
+
+        require!(amount > 0, ErrorCode::InvalidDepositAmount);
+
+        let balance_before = self.program_token_account.amount;
+
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new(
+                self.token_program.to_account_info(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: self.depositor_token_account.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                    to: self.program_token_account.to_account_info(),
+                    authority: self.depositor.to_account_info(),
+                },
+            ),
+            amount,
+            self.mint.decimals,
+        )?;
+
+        // Token-2022 transfer fees are withheld from the amount credited to the program account
+        self.program_token_account.reload()?;
+        let received = self.program_token_account.amount.saturating_sub(balance_before);
+
+        self.sale_config.inventory.deposited = self.sale_config.inventory.deposited
+            .checked_add(received)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, InventoryDeposited {
+            header: EventHeader::new(&self.sale_config, self.depositor.key())?,
+            depositor: self.depositor.key(),
+            amount: received,
+            total_deposited: self.sale_config.inventory.deposited,
+        })?;
+
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ReconcileInventory<'info> {
+
+    pub authority: Signer<'info>,
+
+    // Only the authority may flag a discrepancy, so the event cannot be spammed
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = sale_config.token_mint == mint.key() @ ErrorCode::InvalidTokenMint
+    )]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [SALE_AUTHORITY, sale_config.key().as_ref()],
+        bump = sale_config.bump,
+    )]
+    pub program_sale_authority: SystemAccount<'info>,
+
+    #[account(
+        associated_token::mint = mint,
+        associated_token::authority = program_sale_authority,
+        associated_token::token_program = token_program,
+    )]
+    pub program_token_account: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> ReconcileInventory<'info> {
+    /// Compare the program token balance with the ledger, flagging any difference the program did not see
+    pub fn reconcile(&mut self, event_authority_bump: u8) -> Result<InventoryReport> {
+
+        let balance = self.program_token_account.amount;
+        let expected_balance = self.sale_config.inventory.expected_balance();
+
+        let report = InventoryReport {
+            balance,
+            expected_balance,
+            discrepancy: balance as i128 - expected_balance,
+        };
+
+        if report.discrepancy != 0 {
+            self.sale_config.advance_event_seq()?;
+
+            emit_cpi_event(&self.event_authority, event_authority_bump, InventoryDiscrepancy {
+                header: EventHeader::new(&self.sale_config, self.authority.key())?,
+                report,
+            })?;
+        }
+
+        Ok(report)
+    }
+}
@@ -0,0 +1,69 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use crate::{states::*, events::*, constants::*, error::ErrorCode};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ConfigureMintMode<'info> {
+
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = sale_config.token_mint == mint.key() @ ErrorCode::InvalidTokenMint
+    )]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    pub authority: Signer<'info>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        seeds = [SALE_AUTHORITY, sale_config.key().as_ref()],
+        bump = sale_config.bump,
+    )]
+    pub program_sale_authority: SystemAccount<'info>,
+}
+
+impl<'info> ConfigureMintMode<'info> {
+    /// Switch between selling pre-funded tokens and minting them on purchase up to `max_supply`.
+    /// Pool budgets are reserved from whichever inventory the mode uses.
+    pub fn configure(&mut self, mint_on_purchase: bool, max_supply: u64, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
+
+        if mint_on_purchase {
+            require!(
+                self.mint.mint_authority == Some(self.program_sale_authority.key()).into(),
+                ErrorCode::MintAuthorityMismatch
+            );
+
+            let committed = self.mint.supply
+                .checked_add(self.sale_config.reserved_for_pools())
+                .ok_or(ErrorCode::MathOverflow)?;
+
+            require!(max_supply >= committed, ErrorCode::InvalidMaxSupply);
+        } else {
+            // Back on pre-funded inventory, the ledger has to hold every pool's remaining budget
+            require!(
+                self.sale_config.reserved_for_pools() <= self.sale_config.ledger_inventory(),
+                ErrorCode::InsufficientTokens
+            );
+        }
+
+        let old_mint_on_purchase = self.sale_config.mint_on_purchase;
+        let old_max_supply = self.sale_config.max_supply;
+
+        self.sale_config.mint_on_purchase = mint_on_purchase;
+        self.sale_config.max_supply = max_supply;
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, MintModeSet {
+            header: EventHeader::new(&self.sale_config, self.authority.key())?,
+            old_mint_on_purchase,
+            new_mint_on_purchase: mint_on_purchase,
+            old_max_supply,
+            new_max_supply: max_supply,
+        })?;
+
+        Ok(())
+    }
+}
@@ -1,13 +1,55 @@
 mod admin_control;
+mod advance_schedule;
+mod amend_schedule;
+mod append_tranches;
+mod bonus;
+mod burn_unsold;
 mod buy_tokens;
+mod claim;
+mod claim_for;
+mod claim_grant;
+mod claim_grant_for;
+mod claim_reward;
+mod create_grant;
+mod initialize_monthly_limits;
 mod initialize_sale;
+mod inventory;
+mod mint_mode;
+mod pools;
+mod proceeds_vault;
+mod quote_purchase;
+mod referral;
+mod revoke_grant;
 mod set_monthly_limits;
+mod transfer_position;
+mod validate_monthly_limits;
 mod vesting_controls;
 mod withdraw_tokens;
 
 pub use admin_control::*;
+pub use advance_schedule::*;
+pub use amend_schedule::*;
+pub use append_tranches::*;
+pub use bonus::*;
+pub use burn_unsold::*;
 pub use buy_tokens::*;
+pub use claim::*;
+pub use claim_for::*;
+pub use claim_grant::*;
+pub use claim_grant_for::*;
+pub use claim_reward::*;
+pub use create_grant::*;
+pub use initialize_monthly_limits::*;
 pub use initialize_sale::*;
+pub use inventory::*;
+pub use mint_mode::*;
+pub use pools::*;
+pub use proceeds_vault::*;
+pub use quote_purchase::*;
+pub use referral::*;
+pub use revoke_grant::*;
 pub use set_monthly_limits::*;
+pub use transfer_position::*;
+pub use validate_monthly_limits::*;
 pub use vesting_controls::*;
 pub use withdraw_tokens::*;
@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use crate::{states::*, events::*, error::ErrorCode, budget};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetPoolBudget<'info> {
+
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = sale_config.token_mint == mint.key() @ ErrorCode::InvalidTokenMint
+    )]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    pub authority: Signer<'info>,
+
+    pub mint: InterfaceAccount<'info, Mint>,
+}
+
+impl<'info> SetPoolBudget<'info> {
+    /// Set the lifetime budget of `pool`, reserving the difference from the unreserved inventory
+    pub fn set_budget(&mut self, pool: PoolKind, budget: u64, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
+
+        let unreserved = self.sale_config.unreserved_inventory(self.mint.supply);
+
+        budget::validate_pool_budget(self.sale_config.pool(pool), budget, unreserved)?;
+
+        let old_budget = self.sale_config.pool(pool).budget;
+        self.sale_config.pool_mut(pool).budget = budget;
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, PoolBudgetSet {
+            header: EventHeader::new(&self.sale_config, self.authority.key())?,
+            pool,
+            old_budget,
+            new_budget: budget,
+            drawn: self.sale_config.pool(pool).drawn,
+            unreserved_inventory: self.sale_config.unreserved_inventory(self.mint.supply),
+        })?;
+
+        Ok(())
+    }
+}
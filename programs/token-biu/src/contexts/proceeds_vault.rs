@@ -0,0 +1,174 @@
+use anchor_lang::prelude::*;
+use crate::{states::*, events::*, constants::*, error::ErrorCode, proceeds};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ConfigureProceedsVault<'info> {
+
+    #[account(mut, has_one = authority)]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [PROCEEDS_VAULT, sale_config.key().as_ref()],
+        bump,
+    )]
+    pub proceeds_vault: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> ConfigureProceedsVault<'info> {
+    pub fn configure(&mut self, enabled: bool, treasurer: Pubkey, unlock_timestamp: i64, bump: u8, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
+
+        proceeds::validate_proceeds_vault_update(
+            self.sale_config.proceeds_vault_enabled,
+            &self.sale_config.treasurer,
+            self.sale_config.proceeds_unlock_timestamp,
+            enabled,
+            &treasurer,
+            unlock_timestamp,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        // The vault has to be rent exempt before the first purchase can pay into it
+        let rent_exempt_minimum = Rent::get()?.minimum_balance(0);
+        let top_up = rent_exempt_minimum.saturating_sub(self.proceeds_vault.lamports());
+
+        if enabled && top_up > 0 {
+            anchor_lang::system_program::transfer(
+                CpiContext::new(
+                    self.system_program.to_account_info(),
+                    anchor_lang::system_program::Transfer {
+                        from: self.authority.to_account_info(),
+                        to: self.proceeds_vault.to_account_info(),
+                    },
+                ),
+                top_up,
+            )?;
+        }
+
+        let old_enabled = self.sale_config.proceeds_vault_enabled;
+        let old_treasurer = self.sale_config.treasurer;
+        let old_unlock_timestamp = self.sale_config.proceeds_unlock_timestamp;
+
+        self.sale_config.proceeds_vault_enabled = enabled;
+        self.sale_config.proceeds_vault_bump = bump;
+        self.sale_config.treasurer = treasurer;
+        self.sale_config.proceeds_unlock_timestamp = unlock_timestamp;
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, ProceedsVaultConfigured {
+            header: EventHeader::new(&self.sale_config, self.authority.key())?,
+            old_enabled,
+            new_enabled: enabled,
+            old_treasurer,
+            new_treasurer: treasurer,
+            old_unlock_timestamp,
+            new_unlock_timestamp: unlock_timestamp,
+        })?;
+
+        Ok(())
+    }
+}
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct WithdrawProceeds<'info> {
+
+    #[account(
+        mut,
+        has_one = treasurer @ ErrorCode::Unauthorized,
+        has_one = recipient @ ErrorCode::WrongRecipientAddress,
+    )]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    pub treasurer: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [PROCEEDS_VAULT, sale_config.key().as_ref()],
+        bump = sale_config.proceeds_vault_bump,
+    )]
+    pub proceeds_vault: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub recipient: SystemAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> WithdrawProceeds<'info> {
+    /// Release vault proceeds across the recipient table, `recipient_accounts` lists the table wallets in order
+    pub fn withdraw(&mut self, amount: u64, recipient_accounts: &[AccountInfo<'info>], event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
+
+        let current_timestamp = Clock::get()?.unix_timestamp;
+
+        require!(current_timestamp >= self.sale_config.proceeds_unlock_timestamp, ErrorCode::ProceedsLocked);
+
+        let rent_exempt_minimum = Rent::get()?.minimum_balance(0);
+        let withdrawable = self.proceeds_vault.lamports().saturating_sub(rent_exempt_minimum);
+
+        require!(amount <= withdrawable, ErrorCode::InsufficientFunds);
+
+        let (shares, dust) = self.sale_config.split_proceeds(amount);
+
+        require!(recipient_accounts.len() == shares.len(), ErrorCode::RecipientAccountsMismatch);
+
+        for ((account, table_entry), share) in recipient_accounts.iter().zip(self.sale_config.recipient_shares()).zip(shares) {
+            require_keys_eq!(account.key(), table_entry.wallet, ErrorCode::RecipientAccountsMismatch);
+            if share > 0 {
+                self.transfer_proceeds(account.clone(), share)?;
+            }
+        }
+
+        if dust > 0 {
+            self.transfer_proceeds(self.recipient.to_account_info(), dust)?;
+        }
+
+        self.sale_config.total_proceeds_withdrawn = self.sale_config.total_proceeds_withdrawn
+            .checked_add(amount)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, ProceedsWithdrawn {
+            header: EventHeader::new(&self.sale_config, self.treasurer.key())?,
+            treasurer: self.treasurer.key(),
+            recipient: self.recipient.key(),
+            amount,
+            total_withdrawn: self.sale_config.total_proceeds_withdrawn,
+        })?;
+
+        Ok(())
+    }
+
+    /// Transfer lamports from the vault PDA to `to`
+    fn transfer_proceeds(&self, to: AccountInfo<'info>, amount: u64) -> Result<()> {
+
+        let sale_config_key = self.sale_config.key();
+        let vault_seeds: &[&[u8]] = &[
+            PROCEEDS_VAULT,
+            sale_config_key.as_ref(),
+            &[self.sale_config.proceeds_vault_bump],
+        ];
+
+        anchor_lang::system_program::transfer(
+            CpiContext::new_with_signer(
+                self.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: self.proceeds_vault.to_account_info(),
+                    to,
+                },
+                &[vault_seeds],
+            ),
+            amount,
+        )?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,244 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
+use pyth_solana_receiver_sdk::price_update::PriceUpdateV2;
+use crate::{states::*, constants::*, error::ErrorCode, vesting, budget, purchase};
+use super::buy_tokens::{sol_price_usd, split_purchase, referral_reward_allowance};
+
+#[derive(Accounts)]
+#[instruction(sol_amount: u64, buyer: Pubkey, referrer: Option<Pubkey>)]
+pub struct QuotePurchase<'info> {
+
+    #[account(constraint = sale_config.token_mint == mint.key() @ ErrorCode::InvalidTokenMint)]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    // Left out for a wallet that has never bought in this sale
+    #[account(
+        seeds = [b"wallet_purchase", sale_config.key().as_ref(), buyer.as_ref()],
+        bump,
+    )]
+    pub wallet_purchase: Option<Box<Account<'info, WalletPurchase>>>,
+
+    // Left out for a wallet without a vesting position
+    #[account(
+        seeds = [BUYER_VESTING, sale_config.key().as_ref(), buyer.as_ref()],
+        bump,
+    )]
+    pub buyer_vesting: Option<Box<Account<'info, BuyerVesting>>>,
+
+    #[account(
+        seeds = [MONTHLY_LIMITS, sale_config.key().as_ref(), &[PoolKind::Sale as u8]],
+        bump,
+    )]
+    pub monthly_limits: Box<Account<'info, MonthlyLimits>>,
+
+    #[account(
+        seeds = [MONTHLY_LIMITS, sale_config.key().as_ref(), &[PoolKind::Bonus as u8]],
+        bump,
+    )]
+    pub bonus_limits: Option<Box<Account<'info, MonthlyLimits>>>,
+
+    #[account(
+        seeds = [MONTHLY_LIMITS, sale_config.key().as_ref(), &[PoolKind::Referral as u8]],
+        bump,
+    )]
+    pub referral_limits: Option<Box<Account<'info, MonthlyLimits>>>,
+
+    // Left out for a referrer without referrals yet
+    #[account(
+        seeds = [REFERRER_STATS, sale_config.key().as_ref(), referrer.unwrap_or_default().as_ref()],
+        bump,
+    )]
+    pub referrer_stats: Option<Box<Account<'info, ReferrerStats>>>,
+
+    pub price_update: Box<Account<'info, PriceUpdateV2>>,
+}
+
+impl<'info> QuotePurchase<'info> {
+    /// Price a purchase of `sol_amount` lamports and run the `buy_tokens` checks on it without changing anything
+    pub fn quote(&self, sol_amount: u64, buyer: Pubkey, referrer: Option<Pubkey>) -> Result<PurchaseQuote> {
+
+        let current_timestamp = Clock::get()?.unix_timestamp;
+
+        let sol_price_usd = sol_price_usd(&self.price_update)?;
+        let value_usd = purchase::purchase_value_usd(sol_amount, sol_price_usd);
+        let token_amount = purchase::token_amount(value_usd, self.sale_config.token_price_usd, self.sale_config.mint_decimals);
+
+        let bonus_allowance = self.pool_allowance(PoolKind::Bonus, self.bonus_limits.as_deref().map(|schedule| &**schedule), current_timestamp);
+        let referral_allowance = self.pool_allowance(PoolKind::Referral, self.referral_limits.as_deref().map(|schedule| &**schedule), current_timestamp);
+        let reward_allowance = referral_reward_allowance(&self.sale_config, &self.mint.to_account_info(), referral_allowance.unwrap_or_default())?;
+        let period_allowance = self.period_allowance(current_timestamp);
+
+        let mut quote = PurchaseQuote {
+            token_amount,
+            bonus_amount: purchase::bonus_amount(
+                &self.sale_config.bonus,
+                token_amount,
+                value_usd,
+                current_timestamp,
+                bonus_allowance.unwrap_or_default(),
+            ),
+            token_price_usd: self.sale_config.token_price_usd,
+            sol_price_usd,
+            wallet_allowance: self.wallet_allowance(current_timestamp),
+            period_allowance: period_allowance.unwrap_or_default(),
+            referral_reward: self.referral_reward(referrer, sol_amount, token_amount, reward_allowance),
+            error: None,
+        };
+
+        // Transfer fees the sale pool pays on top of the purchased tokens, worked out the way `buy_tokens` does
+        let delivered_amount = quote.token_amount.saturating_add(quote.bonus_amount);
+        let fee = split_purchase(&self.sale_config, &self.mint.to_account_info(), delivered_amount)?.fee()?;
+
+        quote.error = self.check(&quote, bonus_allowance, period_allowance, fee, current_timestamp)
+            .and_then(|_| self.check_accounts(buyer, referrer, referral_allowance))
+            .err()
+            .map(Into::into);
+
+        Ok(quote)
+    }
+
+    /// The `buy_tokens` checks in the order the purchase runs them, stopping at the first failure
+    fn check(
+        &self,
+        quote: &PurchaseQuote,
+        bonus_allowance: std::result::Result<u64, ErrorCode>,
+        period_allowance: std::result::Result<u64, ErrorCode>,
+        fee: u64,
+        current_timestamp: i64,
+    ) -> std::result::Result<(), ErrorCode> {
+
+        if self.sale_config.paused {
+            return Err(ErrorCode::SalePaused);
+        }
+
+        if self.sale_config.has_ended(current_timestamp) {
+            return Err(ErrorCode::SaleEnded);
+        }
+
+        if self.sale_config.pool(PoolKind::Sale).remaining() < quote.token_amount {
+            return Err(ErrorCode::InsufficientTokens);
+        }
+
+        if self.sale_config.bonus.enabled {
+            bonus_allowance?;
+        }
+
+        let limited_amount = if self.sale_config.bonus.counts_toward_limits {
+            quote.token_amount.checked_add(quote.bonus_amount).ok_or(ErrorCode::MathOverflow)?
+        } else {
+            quote.token_amount
+        };
+
+        if limited_amount > quote.wallet_allowance {
+            return Err(ErrorCode::PurchaseLimitExceeded);
+        }
+
+        if limited_amount > period_allowance? {
+            return Err(ErrorCode::MonthlyLimitExceeded);
+        }
+
+        // Drawn from a copy of the sale pool, the purchased tokens plus the transfer fees
+        let mut sale_pool = *self.sale_config.pool(PoolKind::Sale);
+        budget::draw(&mut sale_pool, quote.token_amount.checked_add(fee).ok_or(ErrorCode::MathOverflow)?)?;
+
+        Ok(())
+    }
+
+    /// The referral and vesting position checks `buy_tokens` runs once the tokens are drawn
+    fn check_accounts(
+        &self,
+        buyer: Pubkey,
+        referrer: Option<Pubkey>,
+        referral_allowance: std::result::Result<u64, ErrorCode>,
+    ) -> std::result::Result<(), ErrorCode> {
+
+        if let (Some(referrer), true) = (referrer, self.sale_config.referral.enabled) {
+            if referrer == buyer {
+                return Err(ErrorCode::SelfReferral);
+            }
+
+            if self.sale_config.referral.reward_kind == ReferralReward::Tokens {
+                referral_allowance?;
+            }
+        }
+
+        if let (true, Some(position)) = (self.sale_config.buyer_vesting.enabled, self.buyer_vesting.as_deref()) {
+            vesting::check_position_sale(position, &self.sale_config.key())?;
+        }
+
+        Ok(())
+    }
+
+    /// Tokens the wallet may still buy before its limit window closes
+    fn wallet_allowance(&self, current_timestamp: i64) -> u64 {
+
+        let purchased = self.wallet_purchase
+            .as_deref()
+            .map_or(0, |wallet| purchase::wallet_window_purchased(wallet, current_timestamp));
+
+        self.sale_config.wallet_purchase_limit.saturating_sub(purchased)
+    }
+
+    /// What the sale schedule would have unlocked by now, or the whole sale pool without vesting
+    fn period_allowance(&self, current_timestamp: i64) -> std::result::Result<u64, ErrorCode> {
+
+        let remaining = self.sale_config.pool(PoolKind::Sale).remaining();
+
+        if !self.monthly_limits.is_vesting_enabled {
+            return Ok(remaining);
+        }
+
+        Ok(vesting::advance(&self.monthly_limits, current_timestamp, remaining)?.tokens_available)
+    }
+
+    /// What the bonus or referral pool can pay right now, worked out the way `buy_tokens` does
+    fn pool_allowance(&self, kind: PoolKind, schedule: Option<&MonthlyLimits>, current_timestamp: i64) -> std::result::Result<u64, ErrorCode> {
+
+        let remaining = self.sale_config.pool(kind).remaining();
+
+        let Some(schedule) = schedule else {
+            if self.sale_config.pool(kind).scheduled {
+                return Err(ErrorCode::PoolScheduleMissing);
+            }
+            return Ok(remaining);
+        };
+
+        if !schedule.is_vesting_enabled {
+            return Ok(remaining);
+        }
+
+        match vesting::advance(schedule, current_timestamp, remaining) {
+            Err(ErrorCode::SaleNotStarted) => Ok(0),
+            unlock => Ok(remaining.min(unlock?.tokens_available)),
+        }
+    }
+
+    /// Reward `buy_tokens` would credit `referrer`, bounded by what the referral pool can send for token rewards
+    fn referral_reward(&self, referrer: Option<Pubkey>, sol_amount: u64, token_amount: u64, referral_allowance: u64) -> u64 {
+
+        let config = &self.sale_config.referral;
+
+        let Some(referrer) = referrer else {
+            return 0;
+        };
+
+        if !config.enabled {
+            return 0;
+        }
+
+        let first_referral = ReferrerStats {
+            referrer,
+            sale: self.sale_config.key(),
+            referral_count: 0,
+            referred_sol: 0,
+            referred_tokens: 0,
+            token_rewards: 0,
+            sol_rewards: 0,
+        };
+        let stats = self.referrer_stats.as_deref().map_or(&first_referral, |stats| &**stats);
+
+        purchase::referral_reward(config, stats, sol_amount, token_amount, referral_allowance)
+    }
+}
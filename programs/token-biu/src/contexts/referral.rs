@@ -0,0 +1,33 @@
+use anchor_lang::prelude::*;
+use crate::{states::*, events::*, constants::*, error::ErrorCode};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ConfigureReferral<'info> {
+
+    #[account(mut, has_one = authority)]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    pub authority: Signer<'info>,
+}
+
+impl<'info> ConfigureReferral<'info> {
+    /// Set the referral terms, token rewards are paid from the referral pool
+    pub fn configure(&mut self, config: ReferralConfig, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
+
+        require!(config.reward_bps <= MAX_REFERRAL_BPS, ErrorCode::InvalidReferralConfig);
+
+        let old_config = self.sale_config.referral;
+        self.sale_config.referral = config;
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, ReferralConfigSet {
+            header: EventHeader::new(&self.sale_config, self.authority.key())?,
+            old_config,
+            new_config: config,
+        })?;
+
+        Ok(())
+    }
+}
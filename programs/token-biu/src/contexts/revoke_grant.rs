@@ -0,0 +1,111 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenInterface, TokenAccount};
+use crate::{states::*, events::*, constants::*, error::ErrorCode, grants};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RevokeGrant<'info> {
+
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = sale_config.token_mint == mint.key() @ ErrorCode::InvalidTokenMint
+    )]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [GRANT, sale_config.key().as_ref(), grant.beneficiary.as_ref()],
+        bump,
+        constraint = grant.sale == sale_config.key() @ ErrorCode::WrongSale,
+    )]
+    pub grant: Box<Account<'info, Grant>>,
+
+    #[account(
+        mut,
+        seeds = [GRANT_VAULT, grant.key().as_ref()],
+        bump,
+    )]
+    pub grant_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [SALE_AUTHORITY, sale_config.key().as_ref()],
+        bump = sale_config.bump,
+    )]
+    pub program_sale_authority: SystemAccount<'info>,
+
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::token_program = token_program,
+        constraint = sale_config.allowed_destinations().contains(&destination.key()) @ ErrorCode::DestinationNotAllowed,
+    )]
+    pub destination: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+impl<'info> RevokeGrant<'info> {
+    /// Stop a revocable grant and return its unvested tokens to a treasury allowlist entry
+    pub fn revoke(&mut self, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
+
+        require!(self.grant.revocable, ErrorCode::GrantNotRevocable);
+        require!(!self.grant.revoked, ErrorCode::GrantAlreadyRevoked);
+
+        let current_timestamp = Clock::get()?.unix_timestamp;
+
+        let tokens_vested = grants::vested_amount(&self.grant, current_timestamp);
+        let tokens_returned = self.grant.total_amount - tokens_vested;
+
+        if tokens_returned > 0 {
+            self.transfer_tokens(tokens_returned)?;
+        }
+
+        self.grant.total_amount = tokens_vested;
+        self.grant.revoked = true;
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, GrantRevoked {
+            header: EventHeader::new(&self.sale_config, self.authority.key())?,
+            beneficiary: self.grant.beneficiary,
+            tokens_vested,
+            tokens_returned,
+            destination: self.destination.key(),
+        })?;
+
+        Ok(())
+    }
+
+    /// Transfer unvested tokens from the grant vault to the destination
+    fn transfer_tokens(&self, token_amount: u64) -> Result<()> {
+
+        let sale_key = self.sale_config.key();
+        let authority_seeds: &[&[u8]] = &[
+            SALE_AUTHORITY,
+            sale_key.as_ref(),
+            &[self.sale_config.bump],
+        ];
+
+        anchor_spl::token_interface::transfer_checked(
+            CpiContext::new_with_signer(
+                self.token_program.to_account_info(),
+                anchor_spl::token_interface::TransferChecked {
+                    from: self.grant_vault.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                    to: self.destination.to_account_info(),
+                    authority: self.program_sale_authority.to_account_info(),
+                },
+                &[authority_seeds]
+            ),
+            token_amount,
+            self.mint.decimals,
+        )?;
+
+        Ok(())
+    }
+}
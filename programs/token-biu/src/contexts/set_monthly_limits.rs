@@ -1,18 +1,21 @@
 use anchor_lang::prelude::*;
-use crate::{states::*, events::*, constants::*};
+use crate::{states::*, events::*, constants::*, error::ErrorCode, vesting};
 
+#[event_cpi]
 #[derive(Accounts)]
+#[instruction(limits: Vec<u64>)]
 pub struct SetMonthlyLimits<'info> {
 
     #[account(mut, has_one = authority)]
     pub sale_config: Box<Account<'info, SaleConfig>>,
 
     #[account(
-        init_if_needed,
-        payer = authority,
-        space = MONTHLY_LIMITS_SIZE,
-        seeds = [b"monthly_limits_a"],
+        mut,
+        seeds = [MONTHLY_LIMITS, sale_config.key().as_ref(), &[monthly_limits.pool as u8]],
         bump,
+        realloc = MonthlyLimits::space(limits.len()),
+        realloc::payer = authority,
+        realloc::zero = false,
     )]
     pub monthly_limits: Box<Account<'info, MonthlyLimits>>,
 
@@ -23,26 +26,52 @@ pub struct SetMonthlyLimits<'info> {
 }
 
 impl<'info> SetMonthlyLimits<'info> {
-    pub fn set_limits(&mut self, limits: [u64; 14], timestamps: [i64; 14]) -> Result<()> {
+    pub fn set_limits(&mut self, limits: Vec<u64>, period: Period, start_timestamp: i64, options: ScheduleOptions, event_authority_bump: u8) -> Result<()> {
 
+        self.sale_config.advance_event_seq()?;
 
-        self.monthly_limits.limits = limits;
-        self.monthly_limits.timestamps = timestamps;
+        let current_timestamp = Clock::get()?.unix_timestamp;
+
+        let timestamps = MonthlyLimits::generate_timestamps(start_timestamp, period.length(), limits.len())
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        vesting::validate_schedule(
+            &timestamps,
+            &limits,
+            current_timestamp,
+            self.sale_config.pool(self.monthly_limits.pool).remaining(),
+            &options,
+        )?;
+
+        let old_limits = std::mem::replace(&mut self.monthly_limits.limits, limits);
+        let old_timestamps = std::mem::replace(&mut self.monthly_limits.timestamps, timestamps);
+        self.monthly_limits.period = period;
         self.monthly_limits.is_vesting_enabled = true;
         self.monthly_limits.tokens_unlocked = DEFAULT;
         self.monthly_limits.tokens_available = DEFAULT;
         self.monthly_limits.last_checked_index = DEFAULT as u8;
         self.monthly_limits.tokens_withdrawn = DEFAULT;
+        self.monthly_limits.tokens_expired = DEFAULT;
 
-        emit!(MonthlyLimitsSet {
-            limits: self.monthly_limits.limits,
-            timestamps: self.monthly_limits.timestamps,
-        });
+        let header = EventHeader::new(&self.sale_config, self.authority.key())?;
 
-        emit!(VestingEnabled {
+        emit_cpi_event(&self.event_authority, event_authority_bump, MonthlyLimitsSet {
+            header,
+            pool: self.monthly_limits.pool,
+            old_limits,
+            old_timestamps,
+            limits: self.monthly_limits.limits.clone(),
+            timestamps: self.monthly_limits.timestamps.clone(),
+            period,
+        })?;
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, VestingEnabled {
+            header,
+            pool: self.monthly_limits.pool,
             vesting: true,
-        });
+        })?;
 
         Ok(())
     }
+
 }
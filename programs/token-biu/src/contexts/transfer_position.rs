@@ -0,0 +1,114 @@
+use anchor_lang::prelude::*;
+use crate::{states::*, events::*, constants::*, error::ErrorCode, purchase};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct TransferPosition<'info> {
+
+    #[account(mut)]
+    pub owner: Signer<'info>,
+
+    /// CHECK: Any wallet can receive a position
+    pub new_owner: UncheckedAccount<'info>,
+
+    #[account(
+        mut,
+        constraint = sale_config.positions_transferable @ ErrorCode::PositionsNotTransferable
+    )]
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+
+    #[account(
+        mut,
+        seeds = [BUYER_VESTING, sale_config.key().as_ref(), owner.key().as_ref()],
+        bump,
+        has_one = owner,
+        constraint = buyer_vesting.sale == sale_config.key() @ ErrorCode::WrongSale,
+    )]
+    pub buyer_vesting: Box<Account<'info, BuyerVesting>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = BUYER_VESTING_SIZE,
+        seeds = [BUYER_VESTING, sale_config.key().as_ref(), new_owner.key().as_ref()],
+        bump,
+    )]
+    pub new_owner_vesting: Box<Account<'info, BuyerVesting>>,
+
+    #[account(
+        mut,
+        seeds = [b"wallet_purchase", sale_config.key().as_ref(), owner.key().as_ref()],
+        bump,
+    )]
+    pub wallet_purchase: Box<Account<'info, WalletPurchase>>,
+
+    #[account(
+        init_if_needed,
+        payer = owner,
+        space = WALLET_PURCHASE_SIZE,
+        seeds = [b"wallet_purchase", sale_config.key().as_ref(), new_owner.key().as_ref()],
+        bump,
+    )]
+    pub new_owner_wallet_purchase: Box<Account<'info, WalletPurchase>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> TransferPosition<'info> {
+    /// Move the whole position into the new owner's position, which is created if needed.
+    /// Release is linear, so merging locked and claimed amounts keeps the vested balance.
+    /// The owner's purchase record in the sale moves along with it.
+    pub fn transfer(&mut self, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
+
+        require_keys_neq!(self.new_owner.key(), self.owner.key(), ErrorCode::InvalidPositionTransfer);
+
+        if self.new_owner_vesting.owner == Pubkey::default() {
+            self.new_owner_vesting.owner = self.new_owner.key();
+            self.new_owner_vesting.sale = self.sale_config.key();
+        }
+
+        require_keys_eq!(self.new_owner_vesting.sale, self.sale_config.key(), ErrorCode::WrongSale);
+
+        let total_locked = self.buyer_vesting.total_locked;
+        let claimed = self.buyer_vesting.claimed;
+
+        self.new_owner_vesting.total_locked = self.new_owner_vesting.total_locked
+            .checked_add(total_locked)
+            .ok_or(ErrorCode::MathOverflow)?;
+        self.new_owner_vesting.claimed = self.new_owner_vesting.claimed
+            .checked_add(claimed)
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        self.buyer_vesting.total_locked = DEFAULT;
+        self.buyer_vesting.claimed = DEFAULT;
+
+        if self.new_owner_wallet_purchase.wallet == Pubkey::default() {
+            self.new_owner_wallet_purchase.wallet = self.new_owner.key();
+            self.new_owner_wallet_purchase.sale = self.sale_config.key();
+        }
+
+        let lifetime_purchased = self.wallet_purchase.lifetime_purchased;
+        let total_spent = self.wallet_purchase.total_spent;
+
+        purchase::merge_wallet_purchase(
+            &mut self.wallet_purchase,
+            &mut self.new_owner_wallet_purchase,
+            Clock::get()?.unix_timestamp,
+        )?;
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, PositionTransferred {
+            header: EventHeader::new(&self.sale_config, self.owner.key())?,
+            sale: self.sale_config.key(),
+            previous_owner: self.owner.key(),
+            new_owner: self.new_owner.key(),
+            total_locked,
+            claimed,
+            lifetime_purchased,
+            total_spent,
+        })?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,27 @@
+use anchor_lang::prelude::*;
+use crate::{states::*, error::ErrorCode, vesting};
+
+#[derive(Accounts)]
+pub struct ValidateMonthlyLimits<'info> {
+
+    pub sale_config: Box<Account<'info, SaleConfig>>,
+}
+
+impl<'info> ValidateMonthlyLimits<'info> {
+    /// Run the `set_monthly_limits` checks for a schedule of `pool` without storing anything
+    pub fn dry_run(&self, pool: PoolKind, limits: Vec<u64>, period: Period, start_timestamp: i64, options: ScheduleOptions) -> Result<ScheduleReport> {
+
+        let current_timestamp = Clock::get()?.unix_timestamp;
+
+        let timestamps = MonthlyLimits::generate_timestamps(start_timestamp, period.length(), limits.len())
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        Ok(vesting::dry_run_schedule(
+            &timestamps,
+            &limits,
+            current_timestamp,
+            self.sale_config.pool(pool).remaining(),
+            &options,
+        ))
+    }
+}
@@ -1,41 +1,95 @@
 
 use anchor_lang::prelude::*;
-use crate::{events::*, states::* };
+use crate::{events::*, states::*, constants::*, error::ErrorCode, vesting};
 
+#[event_cpi]
 #[derive(Accounts)]
 
     pub struct VestingControl<'info> {
 
-    #[account(has_one = authority)]
+    #[account(mut, has_one = authority)]
     pub sale_config: Account<'info, SaleConfig>,
 
     pub authority: Signer<'info>,
 
     
-    #[account(mut)]
+    #[account(
+        mut,
+        seeds = [MONTHLY_LIMITS, sale_config.key().as_ref(), &[monthly_limits.pool as u8]],
+        bump,
+    )]
     pub monthly_limits: Account<'info, MonthlyLimits>,
 
 }
 
 impl<'info> VestingControl<'info> {
-    pub fn enable_vesting(&mut self) -> Result<()> {
+    pub fn enable_vesting(&mut self, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
 
         self.monthly_limits.is_vesting_enabled = true;
         
-        emit!(VestingEnabled {
+        emit_cpi_event(&self.event_authority, event_authority_bump, VestingEnabled {
+            header: EventHeader::new(&self.sale_config, self.authority.key())?,
+            pool: self.monthly_limits.pool,
             vesting: true,
-        });
+        })?;
 
         Ok(())
     }
 
-    pub fn disable_vesting(&mut self) -> Result<()> {
+    pub fn disable_vesting(&mut self, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
 
         self.monthly_limits.is_vesting_enabled = false;
 
-        emit!(VestingDisabled {
+        emit_cpi_event(&self.event_authority, event_authority_bump, VestingDisabled {
+            header: EventHeader::new(&self.sale_config, self.authority.key())?,
+            pool: self.monthly_limits.pool,
             vesting: false,
-        });
+        })?;
+
+        Ok(())
+    }
+
+    pub fn set_rollover_policy(&mut self, policy: RolloverPolicy, event_authority_bump: u8) -> Result<()> {
+
+        self.sale_config.advance_event_seq()?;
+
+        // Periods that ended under the old policy are settled by it before the new one applies
+        self.settle_schedule(event_authority_bump)?;
+
+        let old_policy = self.monthly_limits.rollover_policy;
+        self.monthly_limits.rollover_policy = policy;
+
+        emit_cpi_event(&self.event_authority, event_authority_bump, RolloverPolicySet {
+            header: EventHeader::new(&self.sale_config, self.authority.key())?,
+            pool: self.monthly_limits.pool,
+            old_policy,
+            new_policy: policy,
+        })?;
+
+        Ok(())
+    }
+
+    /// Roll the schedule forward to now under its current policy, when it is running
+    fn settle_schedule(&mut self, event_authority_bump: u8) -> Result<()> {
+
+        if !self.monthly_limits.is_vesting_enabled || self.monthly_limits.timestamps.is_empty() {
+            return Ok(());
+        }
+
+        let current_timestamp = Clock::get()?.unix_timestamp;
+        let inventory = self.sale_config.pool(self.monthly_limits.pool).remaining();
+
+        let unlock = match vesting::advance(&self.monthly_limits, current_timestamp, inventory) {
+            Err(ErrorCode::SaleNotStarted) => return Ok(()),
+            unlock => unlock?,
+        };
+        unlock.apply(&mut self.monthly_limits)?;
+
+        emit_unlock(&self.event_authority, event_authority_bump, EventHeader::new(&self.sale_config, self.authority.key())?, &unlock, &self.monthly_limits)?;
 
         Ok(())
     }
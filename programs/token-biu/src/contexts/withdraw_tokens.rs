@@ -1,155 +1,170 @@
 use anchor_lang::prelude::*;
-use anchor_spl::{
-    associated_token::AssociatedToken,
-    token::{Mint, Token, TokenAccount},
-};
-use crate::{events::*, states::*, constants::*, error::ErrorCode};
+use anchor_spl::token_interface::{Mint, TokenInterface, TokenAccount};
+use crate::{events::*, states::*, constants::*, error::ErrorCode, vesting, budget};
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct WithdrawTokens<'info> {
 
-    #[account(has_one = authority)]
+    #[account(
+        mut,
+        has_one = authority,
+        constraint = sale_config.token_mint == mint.key() @ ErrorCode::InvalidTokenMint
+    )]
     pub sale_config: Account<'info, SaleConfig>,
 
-    #[account(mut)]
     pub authority: Signer<'info>,
 
-    #[account(mut)]
-    pub monthly_limits: Account<'info, MonthlyLimits>,
+    #[account(
+        mut,
+        seeds = [MONTHLY_LIMITS, sale_config.key().as_ref(), &[PoolKind::Treasury as u8]],
+        bump,
+    )]
+    pub monthly_limits: Option<Account<'info, MonthlyLimits>>,
 
-    pub token_program: Program<'info, Token>,
+    pub token_program: Interface<'info, TokenInterface>,
 
     #[account(
         mut,
         associated_token::mint = mint,
-        associated_token::authority = program_sale_authority
+        associated_token::authority = program_sale_authority,
+        associated_token::token_program = token_program,
     )]
-    pub program_token_account: Account<'info, TokenAccount>,
+    pub program_token_account: InterfaceAccount<'info, TokenAccount>,
 
     #[account(
-            seeds = [SALE_AUTHORITY],
+            seeds = [SALE_AUTHORITY, sale_config.key().as_ref()],
             bump = sale_config.bump,
     )]
     pub program_sale_authority: SystemAccount<'info>,
 
-    pub mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub mint: InterfaceAccount<'info, Mint>,
 
     #[account(
-        init_if_needed,
-        payer = authority,
-        associated_token::mint = mint,
-        associated_token::authority = authority,
+        mut,
+        token::mint = mint,
+        token::token_program = token_program,
+        constraint = sale_config.allowed_destinations().contains(&destination.key()) @ ErrorCode::DestinationNotAllowed,
     )]
-    pub admin_token_account: Account<'info, TokenAccount>,
-
-    pub system_program: Program<'info, System>,
-
-    pub associated_token_program: Program<'info, AssociatedToken>,
-
+    pub destination: InterfaceAccount<'info, TokenAccount>,
 }
 
 impl<'info> WithdrawTokens<'info> {
-    pub fn withdraw_remaining_tokens(&mut self, token_amount: u64) -> Result<()>{
+    /// Send `token_amount` from the treasury pool to an allowlisted token account, recording why
+    pub fn withdraw_remaining_tokens(&mut self, token_amount: u64, reason: String, event_authority_bump: u8) -> Result<()>{
+
+        self.sale_config.advance_event_seq()?;
 
         let current_timestamp = Clock::get()?.unix_timestamp;
 
-        self.check_monthly_limits(token_amount, current_timestamp)?;
+        require!(
+            !reason.trim().is_empty() && reason.len() <= MAX_WITHDRAWAL_REASON_LEN,
+            ErrorCode::InvalidWithdrawalReason
+        );
 
-        self.transfer_tokens_to_admin(token_amount)?;
+        require!(self.sale_config.pool(PoolKind::Treasury).remaining() >= token_amount, ErrorCode::PoolBudgetExceeded);
+
+        self.check_monthly_limits(token_amount, current_timestamp, event_authority_bump)?;
+
+        self.transfer_tokens_to_destination(token_amount)?;
 
         self.update_state(token_amount)?;
 
-        emit!(AdminWithdrawnTokens {
+        emit_cpi_event(&self.event_authority, event_authority_bump, AdminWithdrawnTokens {
+            header: EventHeader::new(&self.sale_config, self.authority.key())?,
             tokens_withdrawn: token_amount,
-        });
+            destination: self.destination.key(),
+            reason,
+        })?;
 
         Ok(())
 
     }
 
-    /// Transfer tokens from program to admin
-    fn transfer_tokens_to_admin(&self, token_amount: u64) -> Result<()> {
+    /// Transfer tokens from program to the destination, or mint them there in mint-on-purchase mode
+    fn transfer_tokens_to_destination(&self, token_amount: u64) -> Result<()> {
 
+        let sale_key = self.sale_config.key();
         let authority_seeds: &[&[u8]] = &[
             SALE_AUTHORITY,
+            sale_key.as_ref(),
             &[self.sale_config.bump],
         ];
 
-        anchor_spl::token::transfer(
+        if self.sale_config.mint_on_purchase {
+            anchor_spl::token_interface::mint_to(
+                CpiContext::new_with_signer(
+                    self.token_program.to_account_info(),
+                    anchor_spl::token_interface::MintTo {
+                        mint: self.mint.to_account_info(),
+                        to: self.destination.to_account_info(),
+                        authority: self.program_sale_authority.to_account_info(),
+                    },
+                    &[authority_seeds]
+                ),
+                token_amount,
+            )?;
+
+            return Ok(());
+        }
+
+        anchor_spl::token_interface::transfer_checked(
             CpiContext::new_with_signer(
                 self.token_program.to_account_info(),
-                anchor_spl::token::Transfer {
+                anchor_spl::token_interface::TransferChecked {
                     from: self.program_token_account.to_account_info(),
-                    to: self.admin_token_account.to_account_info(),
+                    mint: self.mint.to_account_info(),
+                    to: self.destination.to_account_info(),
                     authority: self.program_sale_authority.to_account_info(),
                 },
                 &[authority_seeds]
             ),
-            token_amount
+            token_amount,
+            self.mint.decimals,
         )?;
 
         Ok(())
     }
 
-    /// Update state after purchase
+    /// Update state after withdrawal
     fn update_state(&mut self, token_amount: u64) -> Result<()> {
 
-        self.monthly_limits.tokens_unlocked += token_amount;
-        self.monthly_limits.tokens_withdrawn += token_amount;
-        self.monthly_limits.tokens_available -= token_amount;
+        budget::draw(self.sale_config.pool_mut(PoolKind::Treasury), token_amount)?;
+
+        if let Some(schedule) = self.monthly_limits.as_mut() {
+            vesting::release(schedule, token_amount)?;
+            schedule.tokens_withdrawn += token_amount;
+        }
+
+        if !self.sale_config.mint_on_purchase {
+            self.sale_config.inventory.withdrawn = self.sale_config.inventory.withdrawn.checked_add(token_amount).ok_or(ErrorCode::MathOverflow)?;
+        }
 
         Ok(())
     }
 
 
-    /// Check monthly limits
-    fn check_monthly_limits(&mut self, token_amount: u64, current_timestamp: i64) -> Result<()> {
+    /// Check the treasury schedule, if the treasury pool has one and vesting is enabled on it
+    fn check_monthly_limits(&mut self, token_amount: u64, current_timestamp: i64, event_authority_bump: u8) -> Result<()> {
 
-        let timestamps = self.monthly_limits.timestamps;
-        let limits = self.monthly_limits.limits;
+        let Some(schedule) = self.monthly_limits.as_mut() else {
+            require!(!self.sale_config.pool(PoolKind::Treasury).scheduled, ErrorCode::PoolScheduleMissing);
+            return Ok(());
+        };
 
-        if self.monthly_limits.last_checked_index  as usize >= timestamps.len() - 1{
-            self.monthly_limits.tokens_available = self.program_token_account.amount;
+        if !schedule.is_vesting_enabled {
             return Ok(());
         }
 
+        let inventory = self.sale_config.pool(PoolKind::Treasury).remaining();
+        let unlock = vesting::advance(schedule, current_timestamp, inventory)?;
+        unlock.apply(schedule)?;
 
+        emit_unlock(&self.event_authority, event_authority_bump, EventHeader::new(&self.sale_config, self.authority.key())?, &unlock, schedule)?;
 
-        if current_timestamp <= self.monthly_limits.timestamps[self.monthly_limits.last_checked_index  as usize + 1]{
+        unlock.check(token_amount)?;
 
-            if current_timestamp < self.monthly_limits.timestamps[DEFAULT as usize]{
-                return Err(ErrorCode::SaleNotStarted.into());
-            }
-
-            if self.monthly_limits.tokens_available == 0 && self.monthly_limits.tokens_unlocked == 0{
-                self.monthly_limits.tokens_available = limits[self.monthly_limits.last_checked_index as usize];
-            }
-
-
-        require!(
-            token_amount <= self.monthly_limits.tokens_available,
-            ErrorCode::MonthlyLimitExceeded
-        );
-
-            Ok(())
-        }
-
-        else{
-            let mut i = self.monthly_limits.last_checked_index as usize + 1;
-            let mut temp_var  = self.monthly_limits.last_checked_index as usize;
-
-            while i < timestamps.len() && current_timestamp >= timestamps[i]{
-                temp_var = i;
-                self.monthly_limits.tokens_available += limits[i];
-                i += 1;
-            }
-            self.monthly_limits.last_checked_index = temp_var as u8;
-
-        require!(
-            token_amount <= self.monthly_limits.tokens_available,
-            ErrorCode::MonthlyLimitExceeded
-        );
-            Ok(())
-        }
-   }
+        Ok(())
+    }
 }
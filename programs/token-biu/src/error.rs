@@ -1,6 +1,7 @@
 use anchor_lang::prelude::*;
 
 #[error_code]
+#[derive(PartialEq, Eq)]
 pub enum ErrorCode {
 
     #[msg("Token sale is currently paused")]
@@ -47,5 +48,164 @@ pub enum ErrorCode {
 
 	  #[msg("The Withdraw Limit has exceeded")]
     WithdrawLimitExceeded,
+
+    #[msg("Vesting schedule must contain at least one tranche")]
+    EmptySchedule,
+
+    #[msg("Vesting schedule exceeds the maximum number of tranches")]
+    TooManyTranches,
+
+    #[msg("Vesting schedule has not been set")]
+    ScheduleNotSet,
+
+    #[msg("Vesting timestamps must be non-zero")]
+    InvalidTimestamp,
+
+    #[msg("Vesting timestamps must be strictly increasing")]
+    NonMonotonicTimestamps,
+
+    #[msg("Vesting schedule starts in the past")]
+    ScheduleStartsInPast,
+
+    #[msg("Vesting tranche is below the minimum amount")]
+    TrancheBelowMinimum,
+
+    #[msg("Vesting tranche is above the maximum amount")]
+    TrancheAboveMaximum,
+
+    #[msg("Vesting schedule total exceeds the program token inventory")]
+    ScheduleExceedsInventory,
+
+    #[msg("Vesting tranche index is outside the schedule")]
+    TrancheOutOfRange,
+
+    #[msg("Vesting tranche has already been unlocked")]
+    TrancheAlreadyUnlocked,
+
+    #[msg("Invalid buyer vesting configuration")]
+    InvalidBuyerVesting,
+
+    #[msg("Buyer vesting account is required while buyer vesting is enabled")]
+    BuyerVestingAccountMissing,
+
+    #[msg("No vested tokens to claim")]
+    NothingToClaim,
+
+    #[msg("Vesting position or grant belongs to another sale")]
+    WrongSale,
+
+    #[msg("Grant is not revocable")]
+    GrantNotRevocable,
+
+    #[msg("Grant has already been revoked")]
+    GrantAlreadyRevoked,
+
+    #[msg("Vesting positions of this sale are not transferable")]
+    PositionsNotTransferable,
+
+    #[msg("Vesting position cannot be transferred to its current owner")]
+    InvalidPositionTransfer,
+
+    #[msg("Recipient table must have at most the maximum entries, distinct wallets and shares summing to 10000 bps")]
+    InvalidRecipientTable,
+
+    #[msg("Recipient accounts do not match the recipient table")]
+    RecipientAccountsMismatch,
+
+    #[msg("Sale proceeds are still time-locked")]
+    ProceedsLocked,
+
+    #[msg("Claim reward exceeds the maximum")]
+    InvalidClaimReward,
+
+    #[msg("Remaining accounts must be writable (position, owner token account) pairs")]
+    InvalidRemainingAccounts,
+
+    #[msg("Referral reward exceeds the maximum")]
+    InvalidReferralConfig,
+
+    #[msg("Buyer cannot refer themselves")]
+    SelfReferral,
+
+    #[msg("Referrer accounts are missing or incomplete")]
+    ReferralAccountsMissing,
+
+    #[msg("Bonus windows must be ordered ranges and tiers must ascend, with at most 10000 bps each")]
+    InvalidBonusConfig,
+
+    #[msg("The sale authority PDA is not the mint authority")]
+    MintAuthorityMismatch,
+
+    #[msg("Max supply is below the current supply and reserved pools")]
+    InvalidMaxSupply,
+
+    #[msg("Token price must be a positive finite USD amount")]
+    InvalidTokenPrice,
+
+    #[msg("Wallet purchase limit must be greater than zero")]
+    InvalidPurchaseLimit,
+
+    #[msg("Recipient cannot be the default address or the sale authority PDA")]
+    InvalidRecipient,
+
+    #[msg("Program token account is not the sale authority ATA for this mint")]
+    ProgramTokenAccountMismatch,
+
+    #[msg("Program token account does not exist")]
+    ProgramTokenAccountMissing,
+
+    #[msg("Program token account holds no tokens")]
+    ProgramTokenAccountUnfunded,
+
+    #[msg("Deposit amount must be greater than zero")]
+    InvalidDepositAmount,
+
+    #[msg("The sale has ended")]
+    SaleEnded,
+
+    #[msg("The sale has not ended or been finalized yet")]
+    SaleNotEnded,
+
+    #[msg("The sale is already finalized")]
+    SaleAlreadyFinalized,
+
+    #[msg("Unclaimed treasury tranches exceed the remaining treasury budget")]
+    TranchesUnclaimed,
+
+    #[msg("No unsold tokens left to burn")]
+    NothingToBurn,
+
+    #[msg("Amount exceeds the pool's remaining budget")]
+    PoolBudgetExceeded,
+
+    #[msg("Pool budget cannot be lower than what the pool already paid out")]
+    InvalidPoolBudget,
+
+    #[msg("Schedule belongs to a different pool")]
+    WrongPool,
+
+    #[msg("The pool has a schedule that was not passed")]
+    PoolScheduleMissing,
+
+    #[msg("Treasury allowlist must have at most the maximum entries and distinct, non-default accounts")]
+    InvalidTreasuryDestinations,
+
+    #[msg("Destination is not on the treasury allowlist")]
+    DestinationNotAllowed,
+
+    #[msg("Withdrawal reason must be between 1 and 128 bytes")]
+    InvalidWithdrawalReason,
+
+    #[msg("While tokens are locked the buyer vesting schedule can only move earlier")]
+    BuyerVestingLocked,
+
+    #[msg("While proceeds are locked the unlock time can only move later, with the same treasurer and the vault enabled")]
+    ProceedsVaultLocked,
+
+    #[msg("The proceeds vault is enabled but was not passed")]
+    ProceedsVaultMissing,
+
+    #[msg("Mints with a transfer hook are not supported")]
+    TransferHookNotSupported,
 }
 
@@ -1,8 +1,54 @@
 use anchor_lang::prelude::*;
+use anchor_lang::{
+    event::EVENT_IX_TAG_LE,
+    solana_program::{instruction::{AccountMeta, Instruction}, program::invoke_signed},
+};
+use crate::{constants::EVENT_AUTHORITY, states::{BonusConfig, BuyerVestingConfig, InventoryReport, MonthlyLimits, Period, PoolKind, RecipientShare, ReferralConfig, ReferralReward, RolloverPolicy, SaleConfig}, vesting::Unlock};
+
+// Fields every event starts with
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EventHeader {
+    pub sale: Pubkey,
+    pub seq: u64,                   // `SaleConfig::event_seq` of the emitting instruction, shared by all its events
+    pub actor: Pubkey,              // Signer of the instruction that emitted the event
+    pub slot: u64,
+    pub timestamp: i64,
+}
+
+impl EventHeader {
+    pub fn new(sale_config: &Account<SaleConfig>, actor: Pubkey) -> Result<Self> {
+        let clock = Clock::get()?;
+
+        Ok(Self {
+            sale: sale_config.key(),
+            seq: sale_config.event_seq,
+            actor,
+            slot: clock.slot,
+            timestamp: clock.unix_timestamp,
+        })
+    }
+}
+
+// Same self-invocation as `emit_cpi!`, which needs the `Context` while handlers only get the accounts and
+// `ctx.bumps.event_authority`. The event lands in the instruction data, where log truncation cannot drop it.
+pub fn emit_cpi_event<E: anchor_lang::Event>(event_authority: &AccountInfo, event_authority_bump: u8, event: E) -> Result<()> {
+
+    let data: Vec<u8> = EVENT_IX_TAG_LE.into_iter().chain(event.data()).collect();
+    let ix = Instruction::new_with_bytes(
+        crate::ID,
+        &data,
+        vec![AccountMeta::new_readonly(event_authority.key(), true)],
+    );
+
+    invoke_signed(&ix, std::slice::from_ref(event_authority), &[&[EVENT_AUTHORITY, &[event_authority_bump]]])?;
+
+    Ok(())
+}
 
 // Event definitions
 #[event]
 pub struct SaleInitialized {
+    pub header: EventHeader,
     pub authority: Pubkey,
     pub token_price: f64,
     pub recipient: Pubkey,
@@ -10,47 +56,365 @@ pub struct SaleInitialized {
 
 #[event]
 pub struct TokensPurchased {
+    pub header: EventHeader,
     pub buyer: Pubkey,
     pub sol_amount: u64,
     pub token_amount: u64,
+    pub bonus_amount: u64,          // Paid from the bonus pool on top of `token_amount`
     pub sol_price: f64,
+    pub total_sold: u64,            // Sale totals after this purchase
+    pub total_raised: u64,
+    pub buyer_window_purchased: u64, // Buyer totals in this sale after this purchase, the window one as counted toward the wallet limit
+    pub buyer_lifetime_purchased: u64,
+    pub buyer_total_spent: u64,
+}
+
+#[event]
+pub struct ProceedsVaultConfigured {
+    pub header: EventHeader,
+    pub old_enabled: bool,
+    pub new_enabled: bool,
+    pub old_treasurer: Pubkey,
+    pub new_treasurer: Pubkey,
+    pub old_unlock_timestamp: i64,
+    pub new_unlock_timestamp: i64,
+}
+
+#[event]
+pub struct ProceedsWithdrawn {
+    pub header: EventHeader,
+    pub treasurer: Pubkey,
+    pub recipient: Pubkey,
+    pub amount: u64,
+    pub total_withdrawn: u64,
 }
 
 #[event]
 pub struct RecipientChanged {
+    pub header: EventHeader,
     pub old_recipient: Pubkey,
     pub new_recipient: Pubkey,
 }
 
+#[event]
+pub struct RecipientsSet {
+    pub header: EventHeader,
+    pub old_recipients: Vec<RecipientShare>,
+    pub new_recipients: Vec<RecipientShare>,
+}
+
 #[event]
 pub struct TokenAuthorityChanged {
+    pub header: EventHeader,
     pub old_authority: Pubkey,
     pub new_authority: Pubkey,
 }
 
 #[event]
 pub struct WalletLimitSet {
-	pub new_limit: u64,
-	}
-	
+    pub header: EventHeader,
+    pub old_limit: u64,
+    pub new_limit: u64,
+}
+
 #[event]
 pub struct MonthlyLimitsSet {
-    pub limits: [u64; 14],
-    pub timestamps: [i64; 14],
+    pub header: EventHeader,
+    pub pool: PoolKind,
+    pub old_limits: Vec<u64>,
+    pub old_timestamps: Vec<i64>,
+    pub limits: Vec<u64>,
+    pub timestamps: Vec<i64>,
+    pub period: Period,
+}
+
+#[event]
+pub struct TranchesAppended {
+    pub header: EventHeader,
+    pub pool: PoolKind,
+    pub limits: Vec<u64>,
+    pub timestamps: Vec<i64>,
+    pub total_tranches: u64,
+}
+
+#[event]
+pub struct ScheduleAmended {
+    pub header: EventHeader,
+    pub pool: PoolKind,
+    pub start_index: u8,
+    pub old_limits: Vec<u64>,
+    pub new_limits: Vec<u64>,
 }
 
 #[event]
 pub struct VestingEnabled {
+    pub header: EventHeader,
+    pub pool: PoolKind,
     pub vesting: bool,
 }
 
 #[event]
 pub struct VestingDisabled {
+    pub header: EventHeader,
+    pub pool: PoolKind,
     pub vesting: bool,
 }
 
+#[event]
+pub struct RolloverPolicySet {
+    pub header: EventHeader,
+    pub pool: PoolKind,
+    pub old_policy: RolloverPolicy,
+    pub new_policy: RolloverPolicy,
+}
+
+#[event]
+pub struct TokensExpired {
+    pub header: EventHeader,
+    pub pool: PoolKind,
+    pub index: u8,
+    pub tokens_expired: u64,
+    pub policy: RolloverPolicy,
+}
+
+#[event]
+pub struct PeriodAdvanced {
+    pub header: EventHeader,
+    pub pool: PoolKind,
+    pub index: u8,
+    pub timestamp: i64,
+    pub tokens_unlocked: u64,
+    pub tokens_available: u64,
+}
+
+#[event]
+pub struct BuyerVestingSet {
+    pub header: EventHeader,
+    pub old_config: BuyerVestingConfig,
+    pub new_config: BuyerVestingConfig,
+}
+
+#[event]
+pub struct TokensLocked {
+    pub header: EventHeader,
+    pub owner: Pubkey,
+    pub tokens_locked: u64,
+    pub total_locked: u64,
+}
+
+#[event]
+pub struct TokensClaimed {
+    pub header: EventHeader,
+    pub owner: Pubkey,
+    pub claimed_by: Pubkey,
+    pub tokens_claimed: u64,
+    pub cranker_reward: u64,
+    pub total_claimed: u64,
+    pub remaining: u64,
+}
+
+#[event]
+pub struct PositionTransferabilitySet {
+    pub header: EventHeader,
+    pub old_positions_transferable: bool,
+    pub new_positions_transferable: bool,
+}
+
+#[event]
+pub struct PositionTransferred {
+    pub header: EventHeader,
+    pub sale: Pubkey,
+    pub previous_owner: Pubkey,
+    pub new_owner: Pubkey,
+    pub total_locked: u64,
+    pub claimed: u64,
+    pub lifetime_purchased: u64,    // Purchase record totals moved to the new owner
+    pub total_spent: u64,
+}
+
+#[event]
+pub struct GrantCreated {
+    pub header: EventHeader,
+    pub beneficiary: Pubkey,
+    pub start_timestamp: i64,
+    pub total_amount: u64,
+    pub monthly_amount: u64,
+    pub bulk_amount: u64,
+    pub revocable: bool,
+}
+
+#[event]
+pub struct GrantClaimed {
+    pub header: EventHeader,
+    pub beneficiary: Pubkey,
+    pub claimed_by: Pubkey,
+    pub tokens_claimed: u64,
+    pub cranker_reward: u64,
+    pub total_claimed: u64,
+    pub remaining: u64,
+}
+
+#[event]
+pub struct ClaimRewardSet {
+    pub header: EventHeader,
+    pub old_claim_reward_bps: u16,
+    pub new_claim_reward_bps: u16,
+}
+
+#[event]
+pub struct ClaimRewardAccepted {
+    pub header: EventHeader,
+    pub owner: Pubkey,
+    pub account: Pubkey,            // Vesting position or grant the opt-in applies to
+    pub old_accepted_reward_bps: u16,
+    pub new_accepted_reward_bps: u16,
+}
+
+#[event]
+pub struct GrantRevoked {
+    pub header: EventHeader,
+    pub beneficiary: Pubkey,
+    pub tokens_vested: u64,
+    pub tokens_returned: u64,
+    pub destination: Pubkey,        // Treasury allowlist entry the unvested tokens went to
+}
+
 #[event]
 pub struct AdminWithdrawnTokens {
+    pub header: EventHeader,
     pub tokens_withdrawn: u64,
+    pub destination: Pubkey,
+    pub reason: String,
+}
+
+#[event]
+pub struct TreasuryDestinationsSet {
+    pub header: EventHeader,
+    pub old_destinations: Vec<Pubkey>,
+    pub new_destinations: Vec<Pubkey>,
+}
+
+// Emit one event per tranche credited or expired by a schedule step
+pub fn emit_unlock(event_authority: &AccountInfo, event_authority_bump: u8, header: EventHeader, unlock: &Unlock, schedule: &MonthlyLimits) -> Result<()> {
+
+    for (index, tokens_expired) in &unlock.expired {
+        emit_cpi_event(event_authority, event_authority_bump, TokensExpired {
+            header,
+            pool: schedule.pool,
+            index: *index,
+            tokens_expired: *tokens_expired,
+            policy: schedule.rollover_policy,
+        })?;
+    }
+
+    for (index, tokens_unlocked) in &unlock.unlocked {
+        emit_cpi_event(event_authority, event_authority_bump, PeriodAdvanced {
+            header,
+            pool: schedule.pool,
+            index: *index,
+            timestamp: schedule.timestamps[*index as usize],
+            tokens_unlocked: *tokens_unlocked,
+            tokens_available: unlock.tokens_available,
+        })?;
+    }
+
+    Ok(())
+}
+
+#[event]
+pub struct ReferralConfigSet {
+    pub header: EventHeader,
+    pub old_config: ReferralConfig,
+    pub new_config: ReferralConfig,
+}
+
+#[event]
+pub struct ReferralCredited {
+    pub header: EventHeader,
+    pub referrer: Pubkey,
+    pub buyer: Pubkey,
+    pub sol_amount: u64,
+    pub token_amount: u64,
+    pub reward_kind: ReferralReward,
+    pub reward: u64,
+    pub total_rewards: u64,         // Lifetime rewards of this kind for the referrer
+    pub referred_sol: u64,
+    pub referred_tokens: u64,
+}
+
+#[event]
+pub struct BonusConfigSet {
+    pub header: EventHeader,
+    pub old_config: BonusConfig,
+    pub new_config: BonusConfig,
+}
+
+#[event]
+pub struct PoolBudgetSet {
+    pub header: EventHeader,
+    pub pool: PoolKind,
+    pub old_budget: u64,
+    pub new_budget: u64,
+    pub drawn: u64,
+    pub unreserved_inventory: u64,
+}
+
+#[event]
+pub struct MintModeSet {
+    pub header: EventHeader,
+    pub old_mint_on_purchase: bool,
+    pub new_mint_on_purchase: bool,
+    pub old_max_supply: u64,
+    pub new_max_supply: u64,
+}
+
+#[event]
+pub struct InventoryDeposited {
+    pub header: EventHeader,
+    pub depositor: Pubkey,
+    pub amount: u64,                // Received by the program account, net of transfer fees
+    pub total_deposited: u64,
+}
+
+#[event]
+pub struct InventoryDiscrepancy {
+    pub header: EventHeader,
+    pub report: InventoryReport,
 }
 
+#[event]
+pub struct SaleEndSet {
+    pub header: EventHeader,
+    pub old_end_timestamp: i64,
+    pub new_end_timestamp: i64,
+}
+
+#[event]
+pub struct SaleFinalized {
+    pub header: EventHeader,
+    pub total_raised: u64,
+}
+
+#[event]
+pub struct UnsoldBurned {
+    pub header: EventHeader,
+    pub mint: Pubkey,
+    pub program_token_account: Pubkey,
+    pub amount: u64,
+    pub total_burned: u64,
+    pub remaining_balance: u64,     // Left in the program token account for vesting positions and the treasury budget
+    pub mint_supply: u64,           // Mint supply after the burn
+}
+
+#[event]
+pub struct SalePauseSet {
+    pub header: EventHeader,
+    pub old_paused: bool,
+    pub new_paused: bool,
+}
+
+#[event]
+pub struct ScheduleInitialized {
+    pub header: EventHeader,
+    pub pool: PoolKind,
+}
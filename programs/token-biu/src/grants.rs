@@ -0,0 +1,64 @@
+//! Unlock schedule of team and advisor grants.
+
+use crate::states::Grant;
+
+/// Amount of a grant vested at `now`: one monthly unlock per elapsed month plus the bulk unlock
+pub fn vested_amount(grant: &Grant, now: i64) -> u64 {
+
+    let mut vested = 0u64;
+
+    if now >= grant.monthly_start && grant.monthly_count > 0 {
+        let interval = (grant.monthly_end - grant.monthly_start) / grant.monthly_count as i64;
+        let months = if interval > 0 {
+            ((now - grant.monthly_start) / interval).min(grant.monthly_count as i64)
+        } else {
+            grant.monthly_count as i64
+        };
+        vested = vested.saturating_add(grant.monthly_amount.saturating_mul(months as u64));
+    }
+
+    if now >= grant.bulk_timestamp {
+        vested = vested.saturating_add(grant.bulk_amount);
+    }
+
+    // A revoked grant stops at what had vested when it was revoked
+    vested.min(grant.total_amount)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::*;
+
+    const START: i64 = 1_000_000;
+
+    #[test]
+    fn grant_unlocks_monthly_then_in_bulk() {
+        let grant = Grant {
+            beneficiary: Default::default(),
+            sale: Default::default(),
+            total_amount: 12 * 10 + 50,
+            monthly_amount: 10,
+            monthly_start: START,
+            monthly_end: START + GRANT_MONTHLY_SPAN,
+            monthly_count: MONTHS_IN_A_YEAR,
+            bulk_amount: 50,
+            bulk_timestamp: START + GRANT_BULK_DELAY,
+            claimed: 0,
+            revocable: true,
+            revoked: false,
+            accepted_reward_bps: 0,
+        };
+        let month = GRANT_MONTHLY_SPAN / MONTHS_IN_A_YEAR as i64;
+
+        assert_eq!(vested_amount(&grant, START - 1), 0);
+        assert_eq!(vested_amount(&grant, START + month - 1), 0);
+        assert_eq!(vested_amount(&grant, START + 3 * month), 30);
+        assert_eq!(vested_amount(&grant, START + GRANT_MONTHLY_SPAN), 120);
+        assert_eq!(vested_amount(&grant, START + GRANT_BULK_DELAY - 1), 120);
+        assert_eq!(vested_amount(&grant, START + GRANT_BULK_DELAY), 170);
+
+        let revoked = Grant { total_amount: 30, revoked: true, ..grant };
+        assert_eq!(vested_amount(&revoked, START + GRANT_BULK_DELAY), 30);
+    }
+}
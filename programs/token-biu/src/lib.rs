@@ -2,14 +2,21 @@ use anchor_lang::prelude::*;
 #[cfg(not(feature = "no-entrypoint"))]
 use solana_security_txt::security_txt;
 
+pub mod budget;
 pub mod constants;
 pub mod contexts;
 pub mod error;
 pub mod events;
+pub mod grants;
+pub mod proceeds;
+pub mod purchase;
 pub mod states;
+pub mod transfer_fee;
+pub mod vesting;
 
 
 use contexts::*;
+use states::{BonusConfig, BuyerVestingConfig, InventoryReport, Period, PoolKind, PurchaseQuote, RecipientShare, ReferralConfig, RolloverPolicy, ScheduleOptions, ScheduleReport};
 
 declare_id!("LFBbSSCPVnJddba5SbWkJAfPxhdjpqajMTq1LM2Q5A9");
 
@@ -36,49 +43,180 @@ pub mod token_biu {
     pub fn initialize_sale(
         ctx: Context<InitializeSale>,
         token_price_usd: f64,
-        mint_decimals: u64,
         purchase_limit: u64,
     ) -> Result<()> {
-        ctx.accounts.initialize(token_price_usd, mint_decimals, purchase_limit)
+        ctx.accounts.initialize(token_price_usd, purchase_limit, ctx.bumps.event_authority)
     }
 
-    pub fn buy_tokens(ctx: Context<BuyTokens>, sol_amount: u64) -> Result<()> {
-        ctx.accounts.buy(sol_amount)
+    pub fn buy_tokens<'info>(ctx: Context<'_, '_, 'info, 'info, BuyTokens<'info>>, sol_amount: u64) -> Result<()> {
+        ctx.accounts.buy(sol_amount, ctx.remaining_accounts, ctx.bumps.event_authority)
+    }
+
+    pub fn quote_purchase(ctx: Context<QuotePurchase>, sol_amount: u64, buyer: Pubkey, referrer: Option<Pubkey>) -> Result<PurchaseQuote> {
+        ctx.accounts.quote(sol_amount, buyer, referrer)
+    }
+
+    pub fn claim(ctx: Context<Claim>) -> Result<()> {
+        ctx.accounts.claim(ctx.bumps.event_authority)
+    }
+
+    pub fn claim_for(ctx: Context<ClaimFor>) -> Result<()> {
+        ctx.accounts.claim_for(ctx.bumps.event_authority)
+    }
+
+    pub fn claim_for_batch<'info>(ctx: Context<'_, '_, 'info, 'info, ClaimForBatch<'info>>) -> Result<()> {
+        ctx.accounts.claim_for_batch(ctx.remaining_accounts, ctx.bumps.event_authority)
+    }
+
+    pub fn set_position_claim_reward(ctx: Context<SetPositionClaimReward>, accepted_reward_bps: u16) -> Result<()> {
+        ctx.accounts.set_accepted_reward(accepted_reward_bps, ctx.bumps.event_authority)
+    }
+
+    pub fn set_grant_claim_reward(ctx: Context<SetGrantClaimReward>, accepted_reward_bps: u16) -> Result<()> {
+        ctx.accounts.set_accepted_reward(accepted_reward_bps, ctx.bumps.event_authority)
+    }
+
+    pub fn transfer_position(ctx: Context<TransferPosition>) -> Result<()> {
+        ctx.accounts.transfer(ctx.bumps.event_authority)
+    }
+
+    pub fn create_grant(ctx: Context<CreateGrant>, start_timestamp: i64, monthly_unlock: Option<u64>, bulk_unlock: Option<u64>, revocable: bool) -> Result<()> {
+        ctx.accounts.create(start_timestamp, monthly_unlock, bulk_unlock, revocable, ctx.bumps.event_authority)
+    }
+
+    pub fn claim_grant(ctx: Context<ClaimGrant>) -> Result<()> {
+        ctx.accounts.claim(ctx.bumps.event_authority)
+    }
+
+    pub fn claim_grant_for(ctx: Context<ClaimGrantFor>) -> Result<()> {
+        ctx.accounts.claim_for(ctx.bumps.event_authority)
+    }
+
+    pub fn revoke_grant(ctx: Context<RevokeGrant>) -> Result<()> {
+        ctx.accounts.revoke(ctx.bumps.event_authority)
     }
 
     pub fn change_reciepent_account(ctx: Context<AdminControl>, new_receipent: Pubkey) -> Result<()> {
-        ctx.accounts.change_recipient(new_receipent)
+        ctx.accounts.change_recipient(new_receipent, ctx.bumps.event_authority)
     }
 
     pub fn change_config_authority(ctx: Context<AdminControl>, new_authority: Pubkey) -> Result<()> {
-        ctx.accounts.change_authority(new_authority)
+        ctx.accounts.change_authority(new_authority, ctx.bumps.event_authority)
     }
 
     pub fn set_purchase_limit(ctx: Context<AdminControl>, new_limit: u64) -> Result<()> {
-        ctx.accounts.set_limit(new_limit)
+        ctx.accounts.set_limit(new_limit, ctx.bumps.event_authority)
+    }
+
+    pub fn set_recipients(ctx: Context<AdminControl>, recipients: Vec<RecipientShare>) -> Result<()> {
+        ctx.accounts.set_recipients(recipients, ctx.bumps.event_authority)
+    }
+
+    pub fn set_treasury_destinations(ctx: Context<AdminControl>, destinations: Vec<Pubkey>) -> Result<()> {
+        ctx.accounts.set_treasury_destinations(destinations, ctx.bumps.event_authority)
+    }
+
+    pub fn set_buyer_vesting(ctx: Context<AdminControl>, config: BuyerVestingConfig) -> Result<()> {
+        ctx.accounts.set_buyer_vesting(config, ctx.bumps.event_authority)
+    }
+
+    pub fn set_claim_reward(ctx: Context<AdminControl>, claim_reward_bps: u16) -> Result<()> {
+        ctx.accounts.set_claim_reward(claim_reward_bps, ctx.bumps.event_authority)
+    }
+
+    pub fn set_positions_transferable(ctx: Context<AdminControl>, positions_transferable: bool) -> Result<()> {
+        ctx.accounts.set_positions_transferable(positions_transferable, ctx.bumps.event_authority)
+    }
+
+    pub fn configure_proceeds_vault(ctx: Context<ConfigureProceedsVault>, enabled: bool, treasurer: Pubkey, unlock_timestamp: i64) -> Result<()> {
+        ctx.accounts.configure(enabled, treasurer, unlock_timestamp, ctx.bumps.proceeds_vault, ctx.bumps.event_authority)
+    }
+
+    pub fn configure_referral(ctx: Context<ConfigureReferral>, config: ReferralConfig) -> Result<()> {
+        ctx.accounts.configure(config, ctx.bumps.event_authority)
+    }
+
+    pub fn configure_bonus(ctx: Context<ConfigureBonus>, config: BonusConfig) -> Result<()> {
+        ctx.accounts.configure(config, ctx.bumps.event_authority)
+    }
+
+    pub fn set_pool_budget(ctx: Context<SetPoolBudget>, pool: PoolKind, budget: u64) -> Result<()> {
+        ctx.accounts.set_budget(pool, budget, ctx.bumps.event_authority)
+    }
+
+    pub fn configure_mint_mode(ctx: Context<ConfigureMintMode>, mint_on_purchase: bool, max_supply: u64) -> Result<()> {
+        ctx.accounts.configure(mint_on_purchase, max_supply, ctx.bumps.event_authority)
+    }
+
+    pub fn set_sale_end(ctx: Context<AdminControl>, end_timestamp: i64) -> Result<()> {
+        ctx.accounts.set_sale_end(end_timestamp, ctx.bumps.event_authority)
+    }
+
+    pub fn finalize_sale(ctx: Context<AdminControl>) -> Result<()> {
+        ctx.accounts.finalize(ctx.bumps.event_authority)
+    }
+
+    pub fn burn_unsold(ctx: Context<BurnUnsold>) -> Result<()> {
+        ctx.accounts.burn_unsold(ctx.bumps.event_authority)
+    }
+
+    pub fn deposit_inventory(ctx: Context<DepositInventory>, amount: u64) -> Result<()> {
+        ctx.accounts.deposit(amount, ctx.bumps.event_authority)
+    }
+
+    pub fn reconcile_inventory(ctx: Context<ReconcileInventory>) -> Result<InventoryReport> {
+        ctx.accounts.reconcile(ctx.bumps.event_authority)
+    }
+
+    pub fn withdraw_proceeds<'info>(ctx: Context<'_, '_, 'info, 'info, WithdrawProceeds<'info>>, amount: u64) -> Result<()> {
+        ctx.accounts.withdraw(amount, ctx.remaining_accounts, ctx.bumps.event_authority)
     }
 
     pub fn pause_sale(ctx: Context<AdminControl>) -> Result<()> {
-        ctx.accounts.pause()
+        ctx.accounts.pause(ctx.bumps.event_authority)
     }
 
     pub fn resume_sale(ctx: Context<AdminControl>) -> Result<()> {
-        ctx.accounts.resume()
+        ctx.accounts.resume(ctx.bumps.event_authority)
+    }
+
+    pub fn initialize_monthly_limits(ctx: Context<InitializeMonthlyLimits>, pool: PoolKind) -> Result<()> {
+        ctx.accounts.initialize(pool, ctx.bumps.event_authority)
+    }
+
+    pub fn set_monthly_limits(ctx: Context<SetMonthlyLimits>, limits: Vec<u64>, period: Period, start_timestamp: i64, options: ScheduleOptions) -> Result<()> {
+        ctx.accounts.set_limits(limits, period, start_timestamp, options, ctx.bumps.event_authority)
     }
 
-    pub fn set_monthly_limits(ctx: Context<SetMonthlyLimits>, limits: [u64; 14], timestamps: [i64; 14]) -> Result<()> {
-        ctx.accounts.set_limits(limits, timestamps)
+    pub fn validate_monthly_limits(ctx: Context<ValidateMonthlyLimits>, pool: PoolKind, limits: Vec<u64>, period: Period, start_timestamp: i64, options: ScheduleOptions) -> Result<ScheduleReport> {
+        ctx.accounts.dry_run(pool, limits, period, start_timestamp, options)
+    }
+
+    pub fn amend_schedule(ctx: Context<AmendSchedule>, start_index: u8, limits: Vec<u64>, options: ScheduleOptions) -> Result<()> {
+        ctx.accounts.amend(start_index, limits, options, ctx.bumps.event_authority)
+    }
+
+    pub fn append_tranches(ctx: Context<AppendTranches>, limits: Vec<u64>) -> Result<()> {
+        ctx.accounts.append(limits, ctx.bumps.event_authority)
+    }
+
+    pub fn advance_schedule(ctx: Context<AdvanceSchedule>) -> Result<()> {
+        ctx.accounts.advance(ctx.bumps.event_authority)
     }
 
     pub fn enable_vesting(ctx: Context<VestingControl>) -> Result<()> {
-        ctx.accounts.enable_vesting()
+        ctx.accounts.enable_vesting(ctx.bumps.event_authority)
     }
 
     pub fn disable_vesting(ctx: Context<VestingControl>) -> Result<()> {
-        ctx.accounts.disable_vesting()
+        ctx.accounts.disable_vesting(ctx.bumps.event_authority)
+    }
+
+    pub fn set_rollover_policy(ctx: Context<VestingControl>, policy: RolloverPolicy) -> Result<()> {
+        ctx.accounts.set_rollover_policy(policy, ctx.bumps.event_authority)
     }
 
-    pub fn withdraw_tokens(ctx: Context<WithdrawTokens>, token_amount: u64) -> Result<()> {
-        ctx.accounts.withdraw_remaining_tokens(token_amount)
+    pub fn withdraw_tokens(ctx: Context<WithdrawTokens>, token_amount: u64, reason: String) -> Result<()> {
+        ctx.accounts.withdraw_remaining_tokens(token_amount, reason, ctx.bumps.event_authority)
     }
 }
@@ -0,0 +1,72 @@
+//! Checks on where sale proceeds go: the recipient table and the time-locked vault.
+
+use anchor_lang::prelude::Pubkey;
+use crate::error::ErrorCode;
+
+/// Proceeds recipients must be a real wallet, never the default address or the sale's own signing PDA
+pub fn validate_recipient(recipient: &Pubkey, sale_authority: &Pubkey) -> Result<(), ErrorCode> {
+
+    if *recipient == Pubkey::default() || recipient == sale_authority {
+        return Err(ErrorCode::InvalidRecipient);
+    }
+
+    Ok(())
+}
+
+/// Check a proceeds vault update against the current time lock. Until `current_unlock` passes the
+/// unlock time can only move later, and the vault can neither be disabled nor handed to another treasurer.
+pub fn validate_proceeds_vault_update(
+    current_enabled: bool,
+    current_treasurer: &Pubkey,
+    current_unlock: i64,
+    enabled: bool,
+    treasurer: &Pubkey,
+    unlock_timestamp: i64,
+    now: i64,
+) -> Result<(), ErrorCode> {
+
+    if now >= current_unlock {
+        return Ok(());
+    }
+
+    if unlock_timestamp < current_unlock || treasurer != current_treasurer || (current_enabled && !enabled) {
+        return Err(ErrorCode::ProceedsVaultLocked);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: i64 = 1_000_000;
+
+    #[test]
+    fn recipient_cannot_be_default_or_the_sale_authority() {
+        let sale_authority = Pubkey::new_unique();
+
+        assert_eq!(validate_recipient(&Pubkey::new_unique(), &sale_authority), Ok(()));
+        assert_eq!(validate_recipient(&Pubkey::default(), &sale_authority), Err(ErrorCode::InvalidRecipient));
+        assert_eq!(validate_recipient(&sale_authority, &sale_authority), Err(ErrorCode::InvalidRecipient));
+    }
+
+    #[test]
+    fn proceeds_lock_holds_until_the_unlock_time() {
+        let treasurer = Pubkey::new_unique();
+        let other = Pubkey::new_unique();
+        let unlock = START + 1_000;
+
+        // Before the unlock time: only later unlocks with the same treasurer and the vault left on
+        assert_eq!(validate_proceeds_vault_update(true, &treasurer, unlock, true, &treasurer, unlock + 1, START), Ok(()));
+        assert_eq!(validate_proceeds_vault_update(true, &treasurer, unlock, true, &treasurer, 0, START), Err(ErrorCode::ProceedsVaultLocked));
+        assert_eq!(validate_proceeds_vault_update(true, &treasurer, unlock, true, &other, unlock, START), Err(ErrorCode::ProceedsVaultLocked));
+        assert_eq!(validate_proceeds_vault_update(true, &treasurer, unlock, false, &treasurer, unlock, START), Err(ErrorCode::ProceedsVaultLocked));
+
+        // Once unlocked the vault can be reconfigured freely
+        assert_eq!(validate_proceeds_vault_update(true, &treasurer, unlock, false, &other, 0, unlock), Ok(()));
+
+        // A vault that was never locked can be set up with any terms
+        assert_eq!(validate_proceeds_vault_update(false, &Pubkey::default(), 0, true, &treasurer, unlock, START), Ok(()));
+    }
+}
@@ -0,0 +1,199 @@
+//! Pricing and reward math shared by `buy_tokens` and `quote_purchase`.
+
+use crate::{constants::*, error::ErrorCode, states::*};
+
+/// USD value of a payment of `sol_amount` lamports
+pub fn purchase_value_usd(sol_amount: u64, sol_price_usd: f64) -> f64 {
+
+    sol_amount as f64 / 10_f64.powf(SOL_DECIMALS) * sol_price_usd
+}
+
+/// Tokens, in base units of a mint with `decimals`, bought for `value_usd` at `token_price_usd`
+pub fn token_amount(value_usd: f64, token_price_usd: f64, decimals: u64) -> u64 {
+
+    (value_usd / token_price_usd * 10_f64.powf(decimals as f64)) as u64
+}
+
+/// Tokens the wallet bought in its current limit window, which closes after a day without purchases
+pub fn wallet_window_purchased(wallet: &WalletPurchase, now: i64) -> u64 {
+
+    if now - wallet.last_purchased_timestamp > SECONDS_IN_A_DAY {
+        return 0;
+    }
+
+    wallet.total_purchased
+}
+
+/// Move the purchase totals of `from` onto `to` when a position changes hands.
+/// Open limit windows add up and stay open until the later of the two would have closed.
+pub fn merge_wallet_purchase(from: &mut WalletPurchase, to: &mut WalletPurchase, now: i64) -> Result<(), ErrorCode> {
+
+    to.total_purchased = wallet_window_purchased(to, now)
+        .checked_add(wallet_window_purchased(from, now))
+        .ok_or(ErrorCode::MathOverflow)?;
+    to.last_purchased_timestamp = to.last_purchased_timestamp.max(from.last_purchased_timestamp);
+    to.lifetime_purchased = to.lifetime_purchased.checked_add(from.lifetime_purchased).ok_or(ErrorCode::MathOverflow)?;
+    to.total_spent = to.total_spent.checked_add(from.total_spent).ok_or(ErrorCode::MathOverflow)?;
+
+    from.total_purchased = 0;
+    from.lifetime_purchased = 0;
+    from.total_spent = 0;
+
+    Ok(())
+}
+
+/// Bonus tokens for a purchase of `token_amount` worth `value_usd`, made at `now`.
+/// The early-bird and volume bonuses add up and are bounded by the bonus pool.
+pub fn bonus_amount(config: &BonusConfig, token_amount: u64, value_usd: f64, now: i64, bonus_pool: u64) -> u64 {
+
+    if !config.enabled {
+        return 0;
+    }
+
+    let window_bps = config
+        .active_windows()
+        .iter()
+        .find(|window| window.start_timestamp <= now && now < window.end_timestamp)
+        .map_or(0, |window| window.bonus_bps);
+
+    let tier_bps = config
+        .active_tiers()
+        .iter()
+        .rev()
+        .find(|tier| value_usd >= tier.min_usd as f64)
+        .map_or(0, |tier| tier.bonus_bps);
+
+    let bonus_bps = window_bps as u128 + tier_bps as u128;
+    let bonus = (token_amount as u128 * bonus_bps / BPS_DENOMINATOR as u128).min(u64::MAX as u128) as u64;
+
+    bonus.min(bonus_pool)
+}
+
+/// Reward owed to a referrer for a purchase, in the unit of the configured reward kind.
+/// Bounded by the referrer's remaining cap and, for token rewards, by the referral pool.
+pub fn referral_reward(config: &ReferralConfig, stats: &ReferrerStats, sol_amount: u64, token_amount: u64, referral_pool: u64) -> u64 {
+
+    let (base, earned, available) = match config.reward_kind {
+        ReferralReward::Tokens => (token_amount, stats.token_rewards, referral_pool),
+        ReferralReward::Sol => (sol_amount, stats.sol_rewards, sol_amount),
+    };
+
+    let reward = (base as u128 * config.reward_bps as u128 / BPS_DENOMINATOR as u128) as u64;
+
+    reward
+        .min(config.max_reward_per_referrer.saturating_sub(earned))
+        .min(available)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const START: i64 = 1_000_000;
+
+    #[test]
+    fn referral_reward_is_capped_by_referrer_limit_and_pool() {
+        let config = ReferralConfig {
+            enabled: true,
+            reward_bps: 500,
+            reward_kind: ReferralReward::Tokens,
+            max_reward_per_referrer: 120,
+        };
+        let mut stats = ReferrerStats {
+            referrer: Default::default(),
+            sale: Default::default(),
+            referral_count: 0,
+            referred_sol: 0,
+            referred_tokens: 0,
+            token_rewards: 0,
+            sol_rewards: 0,
+        };
+
+        assert_eq!(referral_reward(&config, &stats, 0, 2_000, 1_000), 100);
+        assert_eq!(referral_reward(&config, &stats, 0, 2_000, 40), 40);
+
+        stats.token_rewards = 100;
+        assert_eq!(referral_reward(&config, &stats, 0, 2_000, 1_000), 20);
+
+        // The SOL cap is tracked separately from token rewards
+        let sol = ReferralConfig { reward_kind: ReferralReward::Sol, ..config };
+        assert_eq!(referral_reward(&sol, &stats, 1_000, 2_000, 0), 50);
+    }
+
+    #[test]
+    fn bonus_adds_window_and_highest_tier() {
+        let mut config = BonusConfig {
+            enabled: true,
+            window_count: 1,
+            tier_count: 2,
+            ..Default::default()
+        };
+        config.windows[0] = BonusWindow { start_timestamp: START, end_timestamp: START + 100, bonus_bps: 1_000 };
+        config.tiers[0] = VolumeTier { min_usd: 1_000, bonus_bps: 200 };
+        config.tiers[1] = VolumeTier { min_usd: 10_000, bonus_bps: 500 };
+
+        assert_eq!(bonus_amount(&config, 1_000, 500.0, START, u64::MAX), 100);
+        assert_eq!(bonus_amount(&config, 1_000, 500.0, START + 100, u64::MAX), 0);
+        assert_eq!(bonus_amount(&config, 1_000, 1_000.0, START + 100, u64::MAX), 20);
+        assert_eq!(bonus_amount(&config, 1_000, 25_000.0, START, u64::MAX), 150);
+        assert_eq!(bonus_amount(&config, 1_000, 25_000.0, START, 60), 60);
+
+        config.enabled = false;
+        assert_eq!(bonus_amount(&config, 1_000, 25_000.0, START, u64::MAX), 0);
+    }
+
+    #[test]
+    fn purchase_is_priced_in_token_base_units() {
+        let value_usd = purchase_value_usd(2_000_000_000, 150.0);
+
+        assert_eq!(value_usd, 300.0);
+        assert_eq!(token_amount(value_usd, 0.05, 6), 6_000_000_000);
+        assert_eq!(token_amount(purchase_value_usd(1, 150.0), 0.05, 0), 0);
+    }
+
+    #[test]
+    fn wallet_window_closes_after_a_day_without_purchases() {
+        let wallet = WalletPurchase {
+            wallet: Default::default(),
+            total_purchased: 500,
+            last_purchased_timestamp: START,
+            bump: 0,
+            lifetime_purchased: 800,
+            total_spent: 0,
+            sale: Default::default(),
+        };
+
+        assert_eq!(wallet_window_purchased(&wallet, START + SECONDS_IN_A_DAY), 500);
+        assert_eq!(wallet_window_purchased(&wallet, START + SECONDS_IN_A_DAY + 1), 0);
+    }
+
+    #[test]
+    fn purchase_record_follows_a_transferred_position() {
+        let mut from = WalletPurchase {
+            wallet: Default::default(),
+            total_purchased: 500,
+            last_purchased_timestamp: START,
+            bump: 0,
+            lifetime_purchased: 800,
+            total_spent: 2_000,
+            sale: Default::default(),
+        };
+        let mut to = WalletPurchase {
+            total_purchased: 300,
+            last_purchased_timestamp: START - SECONDS_IN_A_DAY,
+            lifetime_purchased: 300,
+            total_spent: 1_000,
+            ..from.clone()
+        };
+
+        merge_wallet_purchase(&mut from, &mut to, START + 10).unwrap();
+
+        // The new owner's own window has closed, only the moved purchases count toward its limit
+        assert_eq!((to.total_purchased, to.last_purchased_timestamp), (500, START));
+        assert_eq!((to.lifetime_purchased, to.total_spent), (1_100, 3_000));
+
+        // The previous owner keeps no totals, the purchases now count against the new owner
+        assert_eq!((from.total_purchased, from.lifetime_purchased, from.total_spent), (0, 0, 0));
+        assert_eq!(wallet_window_purchased(&from, START + 10), 0);
+    }
+}
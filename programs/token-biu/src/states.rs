@@ -1,9 +1,10 @@
 use anchor_lang::prelude::*;
+use crate::{constants::*, error::ErrorCode, budget};
 
 #[account]
 pub struct SaleConfig {
     pub authority: Pubkey,
-    pub sale_authority: Pubkey,
+    pub sale_authority: Pubkey,     // PDA of [SALE_AUTHORITY, sale], signs for this sale's token account and mint only
     pub recipient: Pubkey,
     pub token_mint: Pubkey,
     pub token_price_usd: f64,
@@ -11,24 +12,405 @@ pub struct SaleConfig {
     pub wallet_purchase_limit: u64,
     pub bump: u8,
     pub paused: bool,
+    pub buyer_vesting: BuyerVestingConfig,
+    pub tokens_locked: u64,         // Purchased tokens still held for buyer vesting positions
+    pub claim_reward_bps: u16,      // Share of a claim paid to whoever cranks it for the owner
+    pub positions_transferable: bool,
+    pub proceeds_vault_enabled: bool,   // Hold proceeds in the vault PDA instead of paying the recipient directly
+    pub proceeds_vault_bump: u8,
+    pub treasurer: Pubkey,              // Role allowed to release proceeds from the vault
+    pub proceeds_unlock_timestamp: i64, // No withdrawals from the vault before this time
+    pub total_raised: u64,              // Lamports paid by buyers so far, net of SOL referral rewards
+    pub total_proceeds_withdrawn: u64,
+    pub recipients: [RecipientShare; MAX_RECIPIENTS], // Proceeds split, `recipient` receives the rounding dust
+    pub recipient_count: u8,
+    pub referral: ReferralConfig,
+    pub bonus: BonusConfig,
+    pub pools: [PoolBudget; POOL_COUNT], // Inventory budgets, indexed by `PoolKind`
+    pub mint_on_purchase: bool,     // Mint sold tokens with the sale authority PDA instead of transferring them
+    pub max_supply: u64,            // Mint supply cap in mint-on-purchase mode
+    pub inventory: InventoryLedger,
+    pub end_timestamp: i64,         // No purchases from this time on, 0 for an open-ended sale
+    pub finalized: bool,
+    pub treasury_destinations: [Pubkey; MAX_TREASURY_DESTINATIONS], // Token accounts admin withdrawals may be sent to
+    pub treasury_destination_count: u8,
+    pub tokens_sold: u64,           // Purchased and bonus tokens delivered to buyers, released or locked
+    pub event_seq: u64,             // Advanced once by every instruction that emits events, carried in each event header
+    pub claim_fee_reserve: u64,     // Drawn from the sale pool at purchase to pay Token-2022 transfer fees on claims of locked tokens
+}
+
+impl SaleConfig {
+    // Each sale has its own signing PDA, so one sale can never move, burn or mint another sale's tokens
+    pub fn find_sale_authority(sale: &Pubkey) -> (Pubkey, u8) {
+        Pubkey::find_program_address(&[SALE_AUTHORITY, sale.as_ref()], &crate::ID)
+    }
+
+    // Step the event sequence for the instruction being processed, every event it emits carries the new value
+    pub fn advance_event_seq(&mut self) -> Result<()> {
+        self.event_seq = self.event_seq.checked_add(1).ok_or(ErrorCode::MathOverflow)?;
+        Ok(())
+    }
+
+    // Purchases are over, either by reaching the end time or by finalization
+    pub fn has_ended(&self, now: i64) -> bool {
+        self.finalized || (self.end_timestamp != 0 && now >= self.end_timestamp)
+    }
+}
+
+impl SaleConfig {
+    // Tokens not assigned to any pool: the ledger inventory left after vesting positions,
+    // or the headroom below `max_supply` in mint-on-purchase mode, minus every pool's remaining budget
+    pub fn unreserved_inventory(&self, supply: u64) -> u64 {
+        let inventory = if self.mint_on_purchase {
+            self.max_supply.saturating_sub(supply)
+        } else {
+            self.ledger_inventory()
+        };
+
+        inventory.saturating_sub(self.reserved_for_pools())
+    }
+
+    // Tokens the ledger holds beyond what vesting positions and their claim fees are owed
+    pub fn ledger_inventory(&self) -> u64 {
+        self.inventory.held().saturating_sub(self.tokens_locked).saturating_sub(self.claim_fee_reserve)
+    }
+
+    // Record a claim of `claimed` locked tokens that took `tokens_sent` out of the program account.
+    // The transfer fees come out of the reserve set aside at purchase, then the sale pool.
+    // Once no tokens are locked any more, whatever is left of the reserve goes back to the sale pool.
+    pub fn settle_claim(&mut self, claimed: u64, tokens_sent: u64) -> Result<()> {
+        let fee = tokens_sent.checked_sub(claimed).ok_or(ErrorCode::MathOverflow)?;
+        budget::charge_claim_fee(&mut self.claim_fee_reserve, &mut self.pools[PoolKind::Sale as usize], fee)?;
+
+        self.tokens_locked = self.tokens_locked.saturating_sub(claimed);
+        self.inventory.claimed = self.inventory.claimed.checked_add(tokens_sent).ok_or(ErrorCode::MathOverflow)?;
+
+        if self.tokens_locked == 0 {
+            let sale_pool = &mut self.pools[PoolKind::Sale as usize];
+            sale_pool.drawn = sale_pool.drawn.saturating_sub(self.claim_fee_reserve);
+            self.claim_fee_reserve = 0;
+        }
+
+        Ok(())
+    }
+
+    // Remaining budgets of every pool
+    pub fn reserved_for_pools(&self) -> u64 {
+        self.pools.iter().fold(0u64, |total, pool| total.saturating_add(pool.remaining()))
+    }
+
+    pub fn pool(&self, kind: PoolKind) -> &PoolBudget {
+        &self.pools[kind as usize]
+    }
+
+    pub fn pool_mut(&mut self, kind: PoolKind) -> &mut PoolBudget {
+        &mut self.pools[kind as usize]
+    }
+
+    // Active entries of the treasury allowlist
+    pub fn allowed_destinations(&self) -> &[Pubkey] {
+        &self.treasury_destinations[..self.treasury_destination_count as usize]
+    }
+
+    // Active entries of the recipient table
+    pub fn recipient_shares(&self) -> &[RecipientShare] {
+        &self.recipients[..self.recipient_count as usize]
+    }
+
+    // Split `amount` across the recipient table, returning each share and the dust left for the primary recipient
+    pub fn split_proceeds(&self, amount: u64) -> (Vec<u64>, u64) {
+        let shares: Vec<u64> = self
+            .recipient_shares()
+            .iter()
+            .map(|share| (amount as u128 * share.bps as u128 / BPS_DENOMINATOR as u128) as u64)
+            .collect();
+        let dust = amount - shares.iter().sum::<u64>();
+
+        (shares, dust)
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PoolKind {
+    Sale,                           // Public purchases
+    Treasury,                       // Admin withdrawals and grant transfer fees
+    Bonus,                          // Purchase bonuses
+    Referral,                       // Referral rewards paid in tokens
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PoolBudget {
+    pub budget: u64,                // Tokens assigned to the pool over its lifetime
+    pub drawn: u64,                 // Tokens paid out of the pool so far
+    pub scheduled: bool,            // A `MonthlyLimits` schedule exists for the pool and has to be passed on draws
+}
+
+impl PoolBudget {
+    pub fn remaining(&self) -> u64 {
+        self.budget.saturating_sub(self.drawn)
+    }
+}
+
+// Token flows in and out of the program token account that the program has seen
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InventoryLedger {
+    pub deposited: u64,             // Received through `deposit_inventory`, or held by the token account at initialization
+    pub minted: u64,                // Locked purchase shares minted into the account in mint-on-purchase mode
+    pub sold: u64,                  // Sent to buyers, including bonus tokens and transfer fees
+    pub claimed: u64,               // Vesting claims, including cranker rewards
+    pub rewarded: u64,              // Referral rewards
+    pub withdrawn: u64,             // Admin withdrawals
+    pub burned: u64,                // Unsold inventory burned at sale end
+}
+
+impl InventoryLedger {
+    // Balance the program token account should hold if every flow went through the program
+    pub fn expected_balance(&self) -> i128 {
+        self.deposited as i128 + self.minted as i128
+            - self.sold as i128
+            - self.claimed as i128
+            - self.rewarded as i128
+            - self.withdrawn as i128
+            - self.burned as i128
+    }
+
+    // Tokens this sale owns according to its own records. Untracked transfers into the token account are not inventory.
+    pub fn held(&self) -> u64 {
+        self.expected_balance().clamp(0, u64::MAX as i128) as u64
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct InventoryReport {
+    pub balance: u64,
+    pub expected_balance: i128,
+    pub discrepancy: i128,          // Balance minus expected, positive for untracked deposits
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct RecipientShare {
+    pub wallet: Pubkey,
+    pub bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BuyerVestingConfig {
+    pub enabled: bool,
+    pub tge_bps: u16,               // Share of each purchase released immediately
+    pub start_timestamp: i64,       // Vesting start (TGE) shared by every position
+    pub cliff_seconds: i64,         // Nothing vests before start + cliff
+    pub duration_seconds: i64,      // Linear release from start over this duration
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BonusConfig {
+    pub enabled: bool,
+    pub windows: [BonusWindow; MAX_BONUS_WINDOWS], // Early-bird periods, the first matching window applies
+    pub window_count: u8,
+    pub tiers: [VolumeTier; MAX_BONUS_TIERS],      // Ascending by `min_usd`, the highest reached tier applies
+    pub tier_count: u8,
+    pub counts_toward_limits: bool, // Bonus tokens count toward the wallet and schedule limits
+}
+
+impl BonusConfig {
+    // Active entries of the window table
+    pub fn active_windows(&self) -> &[BonusWindow] {
+        &self.windows[..self.window_count as usize]
+    }
+
+    // Active entries of the tier table
+    pub fn active_tiers(&self) -> &[VolumeTier] {
+        &self.tiers[..self.tier_count as usize]
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BonusWindow {
+    pub start_timestamp: i64,
+    pub end_timestamp: i64,         // Exclusive
+    pub bonus_bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct VolumeTier {
+    pub min_usd: u64,               // Whole dollars a single purchase has to reach
+    pub bonus_bps: u16,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReferralConfig {
+    pub enabled: bool,
+    pub reward_bps: u16,            // Share of each referred purchase paid to the referrer
+    pub reward_kind: ReferralReward,
+    pub max_reward_per_referrer: u64, // Lifetime cap, in the unit of `reward_kind`
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ReferralReward {
+    #[default]
+    Tokens,                         // Paid from the referral pool, in token base units
+    Sol,                            // Paid from the purchase proceeds, in lamports
+}
+
+#[account]
+pub struct ReferrerStats {
+    pub referrer: Pubkey,
+    pub sale: Pubkey,
+    pub referral_count: u64,
+    pub referred_sol: u64,          // Lamports paid by referred buyers
+    pub referred_tokens: u64,       // Tokens bought by referred buyers
+    pub token_rewards: u64,
+    pub sol_rewards: u64,
+}
+
+#[account]
+pub struct BuyerVesting {
+    pub owner: Pubkey,
+    pub sale: Pubkey,               // Sale whose vesting configuration governs this position
+    pub total_locked: u64,
+    pub claimed: u64,
+    pub accepted_reward_bps: u16,   // Most a cranker may take from claims made for the owner, 0 until they opt in
 }
 
 #[account]
 pub struct WalletPurchase {
     pub wallet: Pubkey,
-    pub total_purchased: u64,
+    pub total_purchased: u64,       // Counted toward the wallet limit, reset after a day without purchases
     pub last_purchased_timestamp: i64,
     pub bump: u8,
+    pub lifetime_purchased: u64,    // Every token bought in the sale, bonus included, never reset
+    pub total_spent: u64,           // Lamports paid to the sale over the wallet's lifetime
+    pub sale: Pubkey,
+}
+
+#[account]
+pub struct Grant {
+    pub beneficiary: Pubkey,
+    pub sale: Pubkey,               // Sale that created the grant, its authority alone may revoke it
+    pub total_amount: u64,          // Reduced to the vested amount when the grant is revoked
+    pub monthly_amount: u64,
+    pub monthly_start: i64,
+    pub monthly_end: i64,           // Last monthly unlock
+    pub monthly_count: u8,
+    pub bulk_amount: u64,
+    pub bulk_timestamp: i64,
+    pub claimed: u64,
+    pub revocable: bool,
+    pub revoked: bool,
+    pub accepted_reward_bps: u16,   // Most a cranker may take from claims made for the beneficiary, 0 until they opt in
 }
 
 #[account]
 pub struct MonthlyLimits {
-    pub timestamps: [i64; 14],
-    pub limits: [u64; 14],
+    pub sale: Pubkey,
+    pub pool: PoolKind,             // Pool whose draws this schedule limits
+    pub timestamps: Vec<i64>,
+    pub limits: Vec<u64>,
     pub tokens_unlocked: u64,       // Tokens unlocked so far
     pub tokens_available: u64,
     pub tokens_withdrawn: u64,
     pub last_checked_index: u8,
     pub is_vesting_enabled: bool,
+    pub period: Period,
+    pub rollover_policy: RolloverPolicy,
+    pub tokens_expired: u64,        // Unsold allocations moved to the reserve by the rollover policy
+}
+
+impl MonthlyLimits {
+    // Account size needed to hold a schedule of `tranches` entries
+    pub const fn space(tranches: usize) -> usize {
+        MONTHLY_LIMITS_BASE_SIZE + tranches * TRANCHE_SIZE
+    }
+
+    // Generate `count` timestamps spaced `period_length` apart, starting at `start_timestamp`
+    pub fn generate_timestamps(start_timestamp: i64, period_length: i64, count: usize) -> Option<Vec<i64>> {
+        (0..count)
+            .map(|i| {
+                period_length
+                    .checked_mul(i as i64)
+                    .and_then(|offset| start_timestamp.checked_add(offset))
+            })
+            .collect()
+    }
+}
+
+/*
+
+Period::Monthly => For monthly periods ~30.44 days
+Period::Daily => For daily periods - 24 hours
+Period::Hourly => For hourly periods - 1 hour
+
+*/
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Period {
+    Hourly,
+    Daily,
+    Monthly,
+}
+
+impl Period {
+    // Method to get the length of the period in seconds
+    pub const fn length(&self) -> i64 {
+        match self {
+            Period::Hourly => 3600,
+            Period::Daily => 86400,
+            Period::Monthly => 2629743,
+        }
+    }
+}
+
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum RolloverPolicy {
+    #[default]
+    CarryOver,                      // Unsold allocations stay available forever
+    Expire,                         // Unsold allocations expire at the end of their period
+    CapPeriods { periods: u8 },     // Carry over at most the allocations of the last `periods` periods
+}
+
+impl RolloverPolicy {
+    // Amount of the tokens carried into tranche `index` that expires under this policy
+    pub fn expired(&self, tokens_available: u64, limits: &[u64], index: usize) -> u64 {
+        let kept = match self {
+            RolloverPolicy::CarryOver => tokens_available,
+            RolloverPolicy::Expire => 0,
+            RolloverPolicy::CapPeriods { periods } => {
+                let first = index.saturating_sub(*periods as usize);
+                let cap = limits[first..index].iter().fold(0u64, |cap, limit| cap.saturating_add(*limit));
+                tokens_available.min(cap)
+            }
+        };
+
+        tokens_available - kept
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default)]
+pub struct ScheduleOptions {
+    pub allow_past_start: bool,     // Accept a schedule whose first tranche is already unlocked
+    pub min_tranche: Option<u64>,
+    pub max_tranche: Option<u64>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ScheduleReport {
+    pub tranche_count: u64,
+    pub total_limits: u64,
+    pub inventory: u64,
+    pub start_timestamp: i64,
+    pub end_timestamp: i64,
+    pub error: Option<u32>,         // Error code of the first failing check
 }
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct PurchaseQuote {
+    pub token_amount: u64,          // Bought at the token price, before the bonus
+    pub bonus_amount: u64,
+    pub token_price_usd: f64,
+    pub sol_price_usd: f64,         // Oracle price the quote was made at
+    pub wallet_allowance: u64,      // Tokens the wallet may still buy in its current limit window
+    pub period_allowance: u64,      // Tokens the sale schedule can hand out right now, the sale pool budget without vesting
+    pub referral_reward: u64,       // Paid to the referrer, in tokens or lamports as the referral config sets
+    pub error: Option<u32>,         // Error code `buy_tokens` would fail with, from the first failing check
+}
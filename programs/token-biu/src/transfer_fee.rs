@@ -0,0 +1,163 @@
+//! Token-2022 transfer fee accounting for tokens sent out of the sale.
+//!
+//! Mints owned by the legacy Token program, and Token-2022 mints without the
+//! transfer fee extension, never charge a fee.
+
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    extension::{transfer_fee::TransferFeeConfig, transfer_hook::TransferHook, BaseStateWithExtensions, StateWithExtensions},
+    state::Mint,
+};
+use crate::error::ErrorCode;
+
+/// Amount to send so that the receiver is credited `net` after the mint's transfer fee for `epoch`
+pub fn gross_up(mint: &AccountInfo, net: u64, epoch: u64) -> Result<u64> {
+
+    if *mint.owner != anchor_spl::token_2022::ID {
+        return Ok(net);
+    }
+
+    let data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<Mint>::unpack(&data)?;
+
+    let Ok(fee_config) = mint_state.get_extension::<TransferFeeConfig>() else {
+        return Ok(net);
+    };
+
+    let fee = fee_config
+        .calculate_inverse_epoch_fee(epoch, net)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    Ok(net.checked_add(fee).ok_or(ErrorCode::MathOverflow)?)
+}
+
+/// Fee the mint withholds from a transfer of `amount` in `epoch`
+pub fn fee(mint: &AccountInfo, amount: u64, epoch: u64) -> Result<u64> {
+
+    if *mint.owner != anchor_spl::token_2022::ID {
+        return Ok(0);
+    }
+
+    let data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<Mint>::unpack(&data)?;
+
+    let Ok(fee_config) = mint_state.get_extension::<TransferFeeConfig>() else {
+        return Ok(0);
+    };
+
+    Ok(fee_config.calculate_epoch_fee(epoch, amount).ok_or(ErrorCode::MathOverflow)?)
+}
+
+/// Whether the mint has the transfer hook extension. Its authority can point it at a program at any time,
+/// and the sale's transfers do not pass the extra accounts such a program needs.
+pub fn has_transfer_hook(mint: &AccountInfo) -> Result<bool> {
+
+    if *mint.owner != anchor_spl::token_2022::ID {
+        return Ok(false);
+    }
+
+    let data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<Mint>::unpack(&data)?;
+
+    Ok(mint_state.get_extension::<TransferHook>().is_ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_spl::token_2022::spl_token_2022::{
+        extension::{transfer_fee::TransferFee, BaseStateWithExtensionsMut, ExtensionType, StateWithExtensionsMut},
+        solana_program::program_pack::Pack,
+    };
+
+    fn legacy_mint() -> Vec<u8> {
+        let mut data = vec![0u8; Mint::LEN];
+        Mint { decimals: 6, is_initialized: true, ..Default::default() }.pack_into_slice(&mut data);
+        data
+    }
+
+    fn fee_mint(basis_points: u16, maximum_fee: u64) -> Vec<u8> {
+        let len = ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferFeeConfig]).unwrap();
+        let mut data = vec![0u8; len];
+        let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+
+        let fee = TransferFee {
+            epoch: 0.into(),
+            maximum_fee: maximum_fee.into(),
+            transfer_fee_basis_points: basis_points.into(),
+        };
+        let config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+        config.older_transfer_fee = fee;
+        config.newer_transfer_fee = fee;
+
+        state.base = Mint { decimals: 6, is_initialized: true, ..Default::default() };
+        state.pack_base();
+        state.init_account_type().unwrap();
+        data
+    }
+
+    fn hook_mint() -> Vec<u8> {
+        let len = ExtensionType::try_calculate_account_len::<Mint>(&[ExtensionType::TransferHook]).unwrap();
+        let mut data = vec![0u8; len];
+        let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+
+        state.init_extension::<TransferHook>(true).unwrap();
+
+        state.base = Mint { decimals: 6, is_initialized: true, ..Default::default() };
+        state.pack_base();
+        state.init_account_type().unwrap();
+        data
+    }
+
+    fn with_mint<T>(owner: Pubkey, mut data: Vec<u8>, f: impl FnOnce(&AccountInfo) -> T) -> T {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mint = AccountInfo::new(&key, false, false, &mut lamports, &mut data, &owner, false, 0);
+        f(&mint)
+    }
+
+    fn gross_up_with(owner: Pubkey, data: Vec<u8>, net: u64) -> u64 {
+        with_mint(owner, data, |mint| gross_up(mint, net, 0).unwrap())
+    }
+
+    #[test]
+    fn legacy_token_mints_charge_no_fee() {
+        assert_eq!(gross_up_with(anchor_spl::token::ID, legacy_mint(), 9_900), 9_900);
+    }
+
+    #[test]
+    fn token_2022_mints_without_fee_extension_charge_no_fee() {
+        assert_eq!(gross_up_with(anchor_spl::token_2022::ID, legacy_mint(), 9_900), 9_900);
+    }
+
+    #[test]
+    fn transfer_fee_is_paid_on_top_of_the_net_amount() {
+        let gross = gross_up_with(anchor_spl::token_2022::ID, fee_mint(100, u64::MAX), 9_900);
+        assert_eq!(gross, 10_000);
+
+        let fee = TransferFee {
+            epoch: 0.into(),
+            maximum_fee: u64::MAX.into(),
+            transfer_fee_basis_points: 100.into(),
+        };
+        assert_eq!(gross - fee.calculate_fee(gross).unwrap(), 9_900);
+    }
+
+    #[test]
+    fn transfer_fee_gross_up_respects_the_maximum_fee() {
+        assert_eq!(gross_up_with(anchor_spl::token_2022::ID, fee_mint(100, 5), 9_900), 9_905);
+    }
+
+    #[test]
+    fn fee_is_what_the_receiver_is_short() {
+        assert_eq!(with_mint(anchor_spl::token_2022::ID, fee_mint(100, u64::MAX), |mint| fee(mint, 10_000, 0).unwrap()), 100);
+        assert_eq!(with_mint(anchor_spl::token::ID, legacy_mint(), |mint| fee(mint, 10_000, 0).unwrap()), 0);
+    }
+
+    #[test]
+    fn transfer_hook_mints_are_detected() {
+        assert!(with_mint(anchor_spl::token_2022::ID, hook_mint(), |mint| has_transfer_hook(mint).unwrap()));
+        assert!(!with_mint(anchor_spl::token_2022::ID, fee_mint(100, 5), |mint| has_transfer_hook(mint).unwrap()));
+        assert!(!with_mint(anchor_spl::token::ID, legacy_mint(), |mint| has_transfer_hook(mint).unwrap()));
+    }
+}
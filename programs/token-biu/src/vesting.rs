@@ -0,0 +1,750 @@
+//! Vesting schedule state machine shared by the buy and withdraw paths.
+//!
+//! Everything in here works on plain `MonthlyLimits` data and a timestamp, so
+//! it can be exercised off-chain without a validator.
+
+use anchor_lang::prelude::Pubkey;
+use crate::{constants::*, error::ErrorCode, states::*};
+
+/// Result of rolling a schedule forward to a given timestamp
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unlock {
+    pub last_checked_index: u8,
+    pub tokens_available: u64,
+    pub unlocked: Vec<(u8, u64)>,   // Tranches credited by this step, as (index, amount)
+    pub expired: Vec<(u8, u64)>,    // Allocations expired by this step, as (index, amount)
+}
+
+impl Unlock {
+    /// Fail if `token_amount` is more than the schedule currently allows
+    pub fn check(&self, token_amount: u64) -> Result<(), ErrorCode> {
+
+        if token_amount > self.tokens_available {
+            return Err(ErrorCode::MonthlyLimitExceeded);
+        }
+
+        Ok(())
+    }
+
+    /// Write the new index and counters back to the schedule
+    pub fn apply(&self, schedule: &mut MonthlyLimits) -> Result<(), ErrorCode> {
+
+        let tokens_expired = self.expired
+            .iter()
+            .try_fold(schedule.tokens_expired, |total, (_, amount)| total.checked_add(*amount))
+            .ok_or(ErrorCode::MathOverflow)?;
+
+        schedule.last_checked_index = self.last_checked_index;
+        schedule.tokens_available = self.tokens_available;
+        schedule.tokens_expired = tokens_expired;
+
+        Ok(())
+    }
+
+    fn expire(&mut self, index: usize, amount: u64) {
+
+        if amount == 0 {
+            return;
+        }
+
+        self.tokens_available -= amount;
+        self.expired.push((index as u8, amount));
+    }
+}
+
+/// Roll `schedule` forward to `now`, crediting every tranche whose timestamp has
+/// passed and applying the rollover policy to unsold allocations.
+///
+/// `inventory` is what the schedule may hand out once its last tranche is
+/// reached under `RolloverPolicy::CarryOver`. The schedule is not modified,
+/// callers persist the result with `Unlock::apply`.
+pub fn advance(schedule: &MonthlyLimits, now: i64, inventory: u64) -> Result<Unlock, ErrorCode> {
+
+    let timestamps = &schedule.timestamps;
+    let limits = &schedule.limits;
+
+    if timestamps.is_empty() || timestamps.len() != limits.len() {
+        return Err(ErrorCode::ScheduleNotSet);
+    }
+
+    let last_index = timestamps.len() - 1;
+    let current_index = schedule.last_checked_index as usize;
+    let policy = schedule.rollover_policy;
+
+    let mut unlock = Unlock {
+        last_checked_index: schedule.last_checked_index,
+        tokens_available: schedule.tokens_available,
+        unlocked: Vec::new(),
+        expired: Vec::new(),
+    };
+
+    // The first tranche is credited lazily on the first release or crank, however late that comes
+    if current_index == 0 && now >= timestamps[0] && nothing_credited(schedule) {
+        unlock.tokens_available = limits[0];
+        unlock.unlocked.push((0, limits[0]));
+    }
+
+    if current_index >= last_index {
+
+        match policy {
+            RolloverPolicy::CarryOver => unlock.tokens_available = inventory,
+            RolloverPolicy::Expire => {
+                if schedule_ended(schedule, now) {
+                    unlock.expire(current_index, unlock.tokens_available);
+                }
+            }
+            RolloverPolicy::CapPeriods { .. } => {}
+        }
+
+        return Ok(unlock);
+    }
+
+    if now <= timestamps[current_index + 1] {
+
+        if now < timestamps[DEFAULT as usize] {
+            return Err(ErrorCode::SaleNotStarted);
+        }
+
+        return Ok(unlock);
+    }
+
+    let mut index = current_index + 1;
+
+    while index < timestamps.len() && now >= timestamps[index] {
+
+        let expired = policy.expired(unlock.tokens_available, limits, index);
+        unlock.expire(index - 1, expired);
+
+        unlock.tokens_available = unlock.tokens_available
+            .checked_add(limits[index])
+            .ok_or(ErrorCode::MathOverflow)?;
+        unlock.unlocked.push((index as u8, limits[index]));
+        unlock.last_checked_index = index as u8;
+
+        index += 1;
+    }
+
+    // Catching up may land past the end of the last tranche's own period
+    if policy == RolloverPolicy::Expire && index > last_index && schedule_ended(schedule, now) {
+        unlock.expire(last_index, unlock.tokens_available);
+    }
+
+    Ok(unlock)
+}
+
+/// No tranche has been credited, released or expired yet
+fn nothing_credited(schedule: &MonthlyLimits) -> bool {
+
+    schedule.tokens_available == 0 && schedule.tokens_unlocked == 0 && schedule.tokens_expired == 0
+}
+
+/// The last tranche's period is over
+fn schedule_ended(schedule: &MonthlyLimits, now: i64) -> bool {
+
+    let last_timestamp = schedule.timestamps[schedule.timestamps.len() - 1];
+
+    now >= last_timestamp.saturating_add(schedule.period.length())
+}
+
+/// Record `token_amount` as released from the schedule
+pub fn release(schedule: &mut MonthlyLimits, token_amount: u64) -> Result<(), ErrorCode> {
+
+    schedule.tokens_unlocked = schedule.tokens_unlocked
+        .checked_add(token_amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    // Releases made while vesting is disabled are not drawn from the allocation
+    schedule.tokens_available = schedule.tokens_available.saturating_sub(token_amount);
+
+    Ok(())
+}
+
+/// Allocation of the tranches credited so far that was neither released nor expired. This is what
+/// `tokens_available` holds, except past the last tranche where carry-over hands out the whole pool.
+pub fn carried_allocation(schedule: &MonthlyLimits) -> u64 {
+
+    schedule.limits
+        .iter()
+        .take(schedule.last_checked_index as usize + 1)
+        .fold(0u64, |total, limit| total.saturating_add(*limit))
+        .saturating_sub(schedule.tokens_unlocked)
+        .saturating_sub(schedule.tokens_expired)
+}
+
+/// Tranche allocations not yet released or expired, the schedule has to be
+/// rolled forward with `advance` first for this to be current
+pub fn unclaimed_allocation(schedule: &MonthlyLimits) -> u64 {
+
+    schedule.limits
+        .iter()
+        .fold(0u64, |total, limit| total.saturating_add(*limit))
+        .saturating_sub(schedule.tokens_unlocked)
+        .saturating_sub(schedule.tokens_expired)
+}
+
+/// Validate a vesting schedule before it is stored.
+///
+/// `inventory` is the amount the schedule can draw from, including tokens
+/// already released under the current schedule.
+pub fn validate_schedule(
+    timestamps: &[i64],
+    limits: &[u64],
+    now: i64,
+    inventory: u64,
+    options: &ScheduleOptions,
+) -> Result<ScheduleReport, ErrorCode> {
+
+    if limits.is_empty() || timestamps.len() != limits.len() {
+        return Err(ErrorCode::EmptySchedule);
+    }
+
+    if limits.len() > MAX_TRANCHES {
+        return Err(ErrorCode::TooManyTranches);
+    }
+
+    if timestamps.iter().any(|timestamp| *timestamp <= 0) {
+        return Err(ErrorCode::InvalidTimestamp);
+    }
+
+    if timestamps.windows(2).any(|pair| pair[0] >= pair[1]) {
+        return Err(ErrorCode::NonMonotonicTimestamps);
+    }
+
+    let start_timestamp = timestamps[0];
+    let end_timestamp = timestamps[timestamps.len() - 1];
+
+    if start_timestamp < now && !options.allow_past_start {
+        return Err(ErrorCode::ScheduleStartsInPast);
+    }
+
+    for limit in limits {
+        if options.min_tranche.is_some_and(|min| *limit < min) {
+            return Err(ErrorCode::TrancheBelowMinimum);
+        }
+        if options.max_tranche.is_some_and(|max| *limit > max) {
+            return Err(ErrorCode::TrancheAboveMaximum);
+        }
+    }
+
+    let total_limits = limits
+        .iter()
+        .try_fold(0u64, |total, limit| total.checked_add(*limit))
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    if total_limits > inventory {
+        return Err(ErrorCode::ScheduleExceedsInventory);
+    }
+
+    Ok(ScheduleReport {
+        tranche_count: limits.len() as u64,
+        total_limits,
+        inventory,
+        start_timestamp,
+        end_timestamp,
+        error: None,
+    })
+}
+
+/// Same checks as `validate_schedule`, but failures are reported instead of returned
+pub fn dry_run_schedule(
+    timestamps: &[i64],
+    limits: &[u64],
+    now: i64,
+    inventory: u64,
+    options: &ScheduleOptions,
+) -> ScheduleReport {
+
+    validate_schedule(timestamps, limits, now, inventory, options).unwrap_or_else(|error| ScheduleReport {
+        tranche_count: limits.len() as u64,
+        total_limits: limits.iter().fold(0u64, |total, limit| total.saturating_add(*limit)),
+        inventory,
+        start_timestamp: timestamps.first().copied().unwrap_or_default(),
+        end_timestamp: timestamps.last().copied().unwrap_or_default(),
+        error: Some(error.into()),
+    })
+}
+
+/// Split a purchase into the part released at TGE and the part locked in the buyer's position
+pub fn split_purchase(config: &BuyerVestingConfig, token_amount: u64) -> (u64, u64) {
+
+    if !config.enabled {
+        return (token_amount, 0);
+    }
+
+    let released = (token_amount as u128 * config.tge_bps as u128 / BPS_DENOMINATOR as u128) as u64;
+
+    (released, token_amount - released)
+}
+
+/// A purchase can only add to a position opened for the same sale, or to a new one
+pub fn check_position_sale(position: &BuyerVesting, sale: &Pubkey) -> Result<(), ErrorCode> {
+
+    if position.owner != Pubkey::default() && position.sale != *sale {
+        return Err(ErrorCode::WrongSale);
+    }
+
+    Ok(())
+}
+
+/// Amount of `total_locked` vested at `now` under a cliff plus linear release
+pub fn vested_amount(config: &BuyerVestingConfig, total_locked: u64, now: i64) -> u64 {
+
+    let elapsed = now.saturating_sub(config.start_timestamp);
+
+    if elapsed < config.cliff_seconds || elapsed < 0 {
+        return 0;
+    }
+
+    if elapsed >= config.duration_seconds {
+        return total_locked;
+    }
+
+    (total_locked as u128 * elapsed as u128 / config.duration_seconds as u128) as u64
+}
+
+/// Check a buyer vesting update against the positions already locked under the current config.
+/// With tokens locked, start, cliff end and release end may only move earlier, so no position vests later.
+pub fn validate_buyer_vesting_update(current: &BuyerVestingConfig, update: &BuyerVestingConfig, tokens_locked: u64) -> Result<(), ErrorCode> {
+
+    if tokens_locked == 0 {
+        return Ok(());
+    }
+
+    let cliff_end = |config: &BuyerVestingConfig| config.start_timestamp.saturating_add(config.cliff_seconds);
+    let release_end = |config: &BuyerVestingConfig| config.start_timestamp.saturating_add(config.duration_seconds);
+
+    if update.enabled != current.enabled
+        || update.start_timestamp > current.start_timestamp
+        || cliff_end(update) > cliff_end(current)
+        || release_end(update) > release_end(current)
+    {
+        return Err(ErrorCode::BuyerVestingLocked);
+    }
+
+    Ok(())
+}
+
+/// Tokens a position owner can claim now
+pub fn claimable(config: &BuyerVestingConfig, position: &BuyerVesting, now: i64) -> u64 {
+
+    vested_amount(config, position.total_locked, now).saturating_sub(position.claimed)
+}
+
+/// Part of a claim paid to the cranker that submitted it: the sale's rate, capped by what the owner accepted
+pub fn cranker_reward(tokens_claimed: u64, claim_reward_bps: u16, accepted_reward_bps: u16) -> u64 {
+
+    let reward_bps = claim_reward_bps.min(accepted_reward_bps);
+
+    (tokens_claimed as u128 * reward_bps as u128 / BPS_DENOMINATOR as u128) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    const START: i64 = 1_000_000;
+    const LENGTH: i64 = 3600;
+
+    fn schedule(limits: &[u64], policy: RolloverPolicy) -> MonthlyLimits {
+        MonthlyLimits {
+            sale: Default::default(),
+            pool: PoolKind::Sale,
+            timestamps: MonthlyLimits::generate_timestamps(START, LENGTH, limits.len()).unwrap(),
+            limits: limits.to_vec(),
+            tokens_unlocked: 0,
+            tokens_available: 0,
+            tokens_withdrawn: 0,
+            last_checked_index: 0,
+            is_vesting_enabled: true,
+            period: Period::Hourly,
+            rollover_policy: policy,
+            tokens_expired: 0,
+        }
+    }
+
+    // A tranche is credited once the clock is past its timestamp
+    fn at(index: i64) -> i64 {
+        START + index * LENGTH
+    }
+
+    #[test]
+    fn empty_schedule_is_rejected() {
+        let schedule = schedule(&[], RolloverPolicy::CarryOver);
+
+        assert_eq!(advance(&schedule, START, 0), Err(ErrorCode::ScheduleNotSet));
+    }
+
+    #[test]
+    fn release_before_start_is_rejected() {
+        let schedule = schedule(&[100, 200], RolloverPolicy::CarryOver);
+
+        assert_eq!(advance(&schedule, START - 1, 0), Err(ErrorCode::SaleNotStarted));
+    }
+
+    #[test]
+    fn first_tranche_is_credited_lazily() {
+        let schedule = schedule(&[100, 200, 300], RolloverPolicy::CarryOver);
+        let unlock = advance(&schedule, START + 10, 0).unwrap();
+
+        assert_eq!(unlock.tokens_available, 100);
+        assert_eq!(unlock.last_checked_index, 0);
+        assert_eq!(unlock.unlocked, vec![(0, 100)]);
+        assert_eq!(unlock.check(101), Err(ErrorCode::MonthlyLimitExceeded));
+        assert_eq!(unlock.check(100), Ok(()));
+    }
+
+    #[test]
+    fn catch_up_credits_every_passed_tranche() {
+        let mut schedule = schedule(&[100, 200, 300, 400], RolloverPolicy::CarryOver);
+        advance(&schedule, START, 0).unwrap().apply(&mut schedule).unwrap();
+
+        let unlock = advance(&schedule, at(2), 0).unwrap();
+
+        assert_eq!(unlock.last_checked_index, 2);
+        assert_eq!(unlock.tokens_available, 600);
+        assert_eq!(unlock.unlocked, vec![(1, 200), (2, 300)]);
+        assert!(unlock.expired.is_empty());
+    }
+
+    #[test]
+    fn first_catch_up_credits_the_first_tranche() {
+        let schedule = schedule(&[100, 200, 300, 400], RolloverPolicy::CarryOver);
+
+        let unlock = advance(&schedule, at(2), 0).unwrap();
+
+        assert_eq!(unlock.last_checked_index, 2);
+        assert_eq!(unlock.tokens_available, 600);
+        assert_eq!(unlock.unlocked, vec![(0, 100), (1, 200), (2, 300)]);
+    }
+
+    #[test]
+    fn late_first_crank_lets_the_whole_allocation_expire() {
+        let mut expiring = schedule(&[100, 200, 300], RolloverPolicy::Expire);
+
+        let unlock = advance(&expiring, at(3), 0).unwrap();
+        unlock.apply(&mut expiring).unwrap();
+
+        assert_eq!(unlock.expired, vec![(0, 100), (1, 200), (2, 300)]);
+        assert_eq!(unclaimed_allocation(&expiring), 0);
+
+        // A single tranche schedule is credited and expired the same way
+        let mut single = schedule(&[100], RolloverPolicy::Expire);
+        advance(&single, at(1), 0).unwrap().apply(&mut single).unwrap();
+        assert_eq!(unclaimed_allocation(&single), 0);
+    }
+
+    #[test]
+    fn carry_over_hands_out_inventory_after_last_tranche() {
+        let mut schedule = schedule(&[100, 200], RolloverPolicy::CarryOver);
+        schedule.last_checked_index = 1;
+        schedule.tokens_available = 50;
+
+        let unlock = advance(&schedule, at(5), 9_999).unwrap();
+
+        assert_eq!(unlock.tokens_available, 9_999);
+    }
+
+    #[test]
+    fn appended_tranches_cap_a_carried_over_schedule_again() {
+        let mut schedule = schedule(&[100, 200], RolloverPolicy::CarryOver);
+        advance(&schedule, at(5), 9_999).unwrap().apply(&mut schedule).unwrap();
+        advance(&schedule, at(5), 9_999).unwrap().apply(&mut schedule).unwrap();
+        release(&mut schedule, 50).unwrap();
+
+        assert_eq!(schedule.tokens_available, 9_949);
+        assert_eq!(carried_allocation(&schedule), 250);
+
+        // Appending resets the pool-wide allowance to what the schedule itself carries
+        schedule.limits.push(300);
+        schedule.timestamps.push(at(6));
+        schedule.tokens_available = schedule.tokens_available.min(carried_allocation(&schedule));
+
+        let unlock = advance(&schedule, at(6) + 1, 9_949).unwrap();
+        assert_eq!(unlock.tokens_available, 550);
+    }
+
+    #[test]
+    fn expire_policy_drops_unsold_allocation() {
+        let mut schedule = schedule(&[100, 200, 300], RolloverPolicy::Expire);
+        advance(&schedule, START, 0).unwrap().apply(&mut schedule).unwrap();
+        release(&mut schedule, 40).unwrap();
+
+        let unlock = advance(&schedule, at(1) + 1, 0).unwrap();
+        unlock.apply(&mut schedule).unwrap();
+
+        assert_eq!(unlock.expired, vec![(0, 60)]);
+        assert_eq!(schedule.tokens_available, 200);
+        assert_eq!(schedule.tokens_expired, 60);
+
+        // The last tranche expires at the end of its own period
+        let unlock = advance(&schedule, at(3), 0).unwrap();
+        unlock.apply(&mut schedule).unwrap();
+
+        assert_eq!(schedule.tokens_available, 0);
+        assert_eq!(schedule.tokens_expired, 60 + 200 + 300);
+    }
+
+    #[test]
+    fn allocation_is_unclaimed_until_released_or_expired() {
+        let mut schedule = schedule(&[100, 200, 300], RolloverPolicy::Expire);
+        advance(&schedule, START, 0).unwrap().apply(&mut schedule).unwrap();
+        release(&mut schedule, 40).unwrap();
+
+        assert_eq!(unclaimed_allocation(&schedule), 560);
+
+        advance(&schedule, at(3), 0).unwrap().apply(&mut schedule).unwrap();
+        assert_eq!(unclaimed_allocation(&schedule), 0);
+    }
+
+    #[test]
+    fn carried_over_allocation_stays_unclaimed_until_released() {
+        let mut schedule = schedule(&[100, 200], RolloverPolicy::CarryOver);
+        release(&mut schedule, 250).unwrap();
+
+        assert_eq!(unclaimed_allocation(&schedule), 50);
+    }
+
+    #[test]
+    fn cap_policy_keeps_the_last_periods_only() {
+        let mut schedule = schedule(&[100, 100, 100, 100], RolloverPolicy::CapPeriods { periods: 1 });
+        advance(&schedule, START, 0).unwrap().apply(&mut schedule).unwrap();
+
+        advance(&schedule, at(1) + 1, 0).unwrap().apply(&mut schedule).unwrap();
+        assert_eq!(schedule.tokens_available, 200);
+
+        let unlock = advance(&schedule, at(2) + 1, 0).unwrap();
+        unlock.apply(&mut schedule).unwrap();
+
+        assert_eq!(unlock.expired, vec![(1, 100)]);
+        assert_eq!(schedule.tokens_available, 200);
+    }
+
+    #[test]
+    fn policy_change_applies_only_after_the_schedule_is_settled() {
+        let mut settled = schedule(&[100, 200, 300], RolloverPolicy::CarryOver);
+        advance(&settled, START, 0).unwrap().apply(&mut settled).unwrap();
+        let mut unsettled = settled.clone();
+
+        // Rolled forward under carry-over before the switch, the first period's allocation is kept
+        advance(&settled, at(1) + 1, 0).unwrap().apply(&mut settled).unwrap();
+        settled.rollover_policy = RolloverPolicy::Expire;
+        let unlock = advance(&settled, at(1) + 2, 0).unwrap();
+
+        assert!(unlock.expired.is_empty());
+        assert_eq!(unlock.tokens_available, 300);
+
+        // Switched first, the ended period would be expired retroactively
+        unsettled.rollover_policy = RolloverPolicy::Expire;
+        let unlock = advance(&unsettled, at(1) + 1, 0).unwrap();
+
+        assert_eq!(unlock.expired, vec![(0, 100)]);
+    }
+
+    #[test]
+    fn release_without_allocation_does_not_underflow() {
+        let mut schedule = schedule(&[100], RolloverPolicy::CarryOver);
+
+        release(&mut schedule, 500).unwrap();
+
+        assert_eq!(schedule.tokens_available, 0);
+        assert_eq!(schedule.tokens_unlocked, 500);
+    }
+
+    #[test]
+    fn validation_reports_first_failure() {
+        let options = ScheduleOptions::default();
+
+        assert_eq!(
+            validate_schedule(&[10, 10], &[1, 1], 0, 10, &options),
+            Err(ErrorCode::NonMonotonicTimestamps)
+        );
+        assert_eq!(
+            validate_schedule(&[0, 10], &[1, 1], 0, 10, &options),
+            Err(ErrorCode::InvalidTimestamp)
+        );
+        assert_eq!(
+            validate_schedule(&[10, 20], &[1, 1], 15, 10, &options),
+            Err(ErrorCode::ScheduleStartsInPast)
+        );
+        assert_eq!(
+            validate_schedule(&[10, 20], &[6, 6], 0, 10, &options),
+            Err(ErrorCode::ScheduleExceedsInventory)
+        );
+        assert_eq!(
+            validate_schedule(&[10, 20], &[1, 9], 0, 10, &ScheduleOptions { max_tranche: Some(5), ..options }),
+            Err(ErrorCode::TrancheAboveMaximum)
+        );
+
+        let report = dry_run_schedule(&[10, 20], &[6, 6], 0, 10, &options);
+        assert_eq!(report.error, Some(ErrorCode::ScheduleExceedsInventory.into()));
+        assert_eq!(report.total_limits, 12);
+    }
+
+    #[test]
+    fn buyer_vesting_releases_after_cliff_then_linearly() {
+        let config = BuyerVestingConfig {
+            enabled: true,
+            tge_bps: 2_000,
+            start_timestamp: START,
+            cliff_seconds: 100,
+            duration_seconds: 1_000,
+        };
+
+        assert_eq!(split_purchase(&config, 1_000), (200, 800));
+        assert_eq!(split_purchase(&BuyerVestingConfig::default(), 1_000), (1_000, 0));
+
+        assert_eq!(vested_amount(&config, 800, START - 1), 0);
+        assert_eq!(vested_amount(&config, 800, START + 99), 0);
+        assert_eq!(vested_amount(&config, 800, START + 100), 80);
+        assert_eq!(vested_amount(&config, 800, START + 500), 400);
+        assert_eq!(vested_amount(&config, 800, START + 5_000), 800);
+    }
+
+    #[test]
+    fn purchases_only_add_to_positions_of_the_same_sale() {
+        let sale = Pubkey::new_unique();
+        let mut position = BuyerVesting {
+            owner: Pubkey::default(),
+            sale: Pubkey::default(),
+            total_locked: 0,
+            claimed: 0,
+            accepted_reward_bps: 0,
+        };
+
+        assert_eq!(check_position_sale(&position, &sale), Ok(()));
+
+        position.owner = Pubkey::new_unique();
+        position.sale = sale;
+        assert_eq!(check_position_sale(&position, &sale), Ok(()));
+
+        position.sale = Pubkey::new_unique();
+        assert_eq!(check_position_sale(&position, &sale), Err(ErrorCode::WrongSale));
+    }
+
+    #[test]
+    fn locked_buyer_vesting_only_moves_earlier() {
+        let config = BuyerVestingConfig {
+            enabled: true,
+            tge_bps: 2_000,
+            start_timestamp: START,
+            cliff_seconds: 100,
+            duration_seconds: 1_000,
+        };
+
+        // Nothing locked yet, any valid config goes
+        let later = BuyerVestingConfig { start_timestamp: START + 1, ..config };
+        assert_eq!(validate_buyer_vesting_update(&config, &later, 0), Ok(()));
+
+        assert_eq!(validate_buyer_vesting_update(&config, &later, 1), Err(ErrorCode::BuyerVestingLocked));
+        let longer_cliff = BuyerVestingConfig { cliff_seconds: 101, ..config };
+        assert_eq!(validate_buyer_vesting_update(&config, &longer_cliff, 1), Err(ErrorCode::BuyerVestingLocked));
+        let longer_release = BuyerVestingConfig { duration_seconds: 1_001, ..config };
+        assert_eq!(validate_buyer_vesting_update(&config, &longer_release, 1), Err(ErrorCode::BuyerVestingLocked));
+        let disabled = BuyerVestingConfig { enabled: false, ..config };
+        assert_eq!(validate_buyer_vesting_update(&config, &disabled, 1), Err(ErrorCode::BuyerVestingLocked));
+
+        // An earlier start keeping the same end dates vests every position at least as fast
+        let earlier = BuyerVestingConfig { start_timestamp: START - 50, cliff_seconds: 150, duration_seconds: 1_050, ..config };
+        assert_eq!(validate_buyer_vesting_update(&config, &earlier, 1), Ok(()));
+        for now in (START - 100..START + 1_200).step_by(25) {
+            assert!(vested_amount(&earlier, 800, now) >= vested_amount(&config, 800, now));
+        }
+    }
+
+    #[test]
+    fn cranker_reward_needs_the_owner_to_opt_in() {
+        assert_eq!(cranker_reward(10_000, MAX_CLAIM_REWARD_BPS, 0), 0);
+        assert_eq!(cranker_reward(10_000, MAX_CLAIM_REWARD_BPS, 50), 50);
+        assert_eq!(cranker_reward(10_000, 20, MAX_CLAIM_REWARD_BPS), 20);
+        assert_eq!(cranker_reward(10_000, 0, MAX_CLAIM_REWARD_BPS), 0);
+    }
+
+    fn policy() -> impl Strategy<Value = RolloverPolicy> {
+        prop_oneof![
+            Just(RolloverPolicy::CarryOver),
+            Just(RolloverPolicy::Expire),
+            (0u8..4).prop_map(|periods| RolloverPolicy::CapPeriods { periods }),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn schedule_never_hands_out_more_than_it_unlocks(
+            limits in prop::collection::vec(0u64..1_000_000, 1..24),
+            policy in policy(),
+            steps in prop::collection::vec((0i64..2 * LENGTH, 0u64..2_000_000), 1..32),
+        ) {
+            let mut schedule = schedule(&limits, policy);
+            let mut now = START;
+
+            for (elapsed, requested) in steps {
+                now += elapsed;
+                let previous_index = schedule.last_checked_index;
+
+                let unlock = advance(&schedule, now, 0).unwrap();
+                unlock.apply(&mut schedule).unwrap();
+
+                prop_assert!(schedule.last_checked_index >= previous_index);
+                prop_assert!((schedule.last_checked_index as usize) < limits.len());
+                prop_assert!(schedule.timestamps[schedule.last_checked_index as usize] <= now);
+
+                if unlock.check(requested).is_ok() {
+                    release(&mut schedule, requested).unwrap();
+                } else {
+                    prop_assert!(requested > schedule.tokens_available);
+                }
+
+                // Inventory is zero, so nothing beyond the credited tranches can leave
+                let credited: u64 = limits[..=schedule.last_checked_index as usize].iter().sum();
+                let accounted = schedule.tokens_available + schedule.tokens_unlocked + schedule.tokens_expired;
+                prop_assert!(accounted <= credited);
+
+                // And nothing credited goes missing, except the allocation carry-over hands to the inventory at the end
+                let carried_over = policy == RolloverPolicy::CarryOver && schedule.last_checked_index as usize == limits.len() - 1;
+                if !carried_over {
+                    prop_assert!(accounted >= credited);
+                }
+
+                if let RolloverPolicy::CapPeriods { periods } = policy {
+                    let last = schedule.last_checked_index as usize;
+                    let cap: u64 = limits[last.saturating_sub(periods as usize)..=last].iter().sum();
+                    prop_assert!(schedule.tokens_available <= cap);
+                }
+            }
+        }
+
+        #[test]
+        fn buyer_vesting_is_monotonic_and_bounded(
+            total in 0u64..u64::MAX,
+            cliff in 0i64..1_000,
+            duration in 0i64..10_000,
+            first in 0i64..20_000,
+            second in 0i64..20_000,
+        ) {
+            let config = BuyerVestingConfig {
+                enabled: true,
+                tge_bps: 0,
+                start_timestamp: START,
+                cliff_seconds: cliff,
+                duration_seconds: duration,
+            };
+            let (earlier, later) = (first.min(second), first.max(second));
+
+            prop_assert!(vested_amount(&config, total, START + earlier) <= vested_amount(&config, total, START + later));
+            prop_assert!(vested_amount(&config, total, START + later) <= total);
+        }
+
+        #[test]
+        fn generated_schedules_pass_ordering_checks(
+            limits in prop::collection::vec(1u64..1_000, 1..MAX_TRANCHES),
+            length in 1i64..10_000_000,
+        ) {
+            let timestamps = MonthlyLimits::generate_timestamps(START, length, limits.len()).unwrap();
+            let report = validate_schedule(&timestamps, &limits, START, u64::MAX, &ScheduleOptions::default()).unwrap();
+
+            prop_assert_eq!(report.tranche_count as usize, limits.len());
+            prop_assert_eq!(report.end_timestamp, START + length * (limits.len() as i64 - 1));
+        }
+    }
+}
@@ -7,8 +7,13 @@ import {
   getAssociatedTokenAddress,
   mintTo,
   TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
+  ExtensionType,
+  getMintLen,
   getAssociatedTokenAddressSync,
   createAssociatedTokenAccountInstruction,
+  createInitializeMintInstruction,
+  createInitializeTransferFeeConfigInstruction,
   getAccount,
 } from "@solana/spl-token";
 import {
@@ -41,6 +46,9 @@ describe("token_biu", () => {
   );
   const recipient = new anchor.web3.PublicKey("6Aa35EE5yEoCuRPgdhzXdvYKGFVLXeeLjDBx6h35g3oh");
 
+  // Pyth SOL/USD price update account, cloned into the local validator from mainnet
+  const SOL_USD_PRICE_UPDATE = new anchor.web3.PublicKey("7UVimffxr9ow1uXYxsr4LHAcV58mLzhmwaeKvJ1pjLiE");
+
   let saleConfig: Keypair;
   saleConfig = anchor.web3.Keypair.generate();
   let mint: anchor.web3.PublicKey;
@@ -109,7 +117,7 @@ describe("token_biu", () => {
     try {
       console.log("\n--- Finding program sale authority PDA ---");
       const [authority, bump] = anchor.web3.PublicKey.findProgramAddressSync(
-        [Buffer.from("SALE_AUTHORITY")],
+        [Buffer.from("SALE_AUTHORITY"), saleConfig.publicKey.toBuffer()],
         program.programId
       );
       programSaleAuthority = authority;
@@ -162,23 +170,53 @@ describe("token_biu", () => {
       throw error;
     }
 
+    try {
+      console.log("\n--- Minting tokens to program token account ---");
+      // Add extra tokens for safety
+      const MINT_AMOUNT = totalMonthlyLimits * 500;
+
+      console.log(`Minting ${MINT_AMOUNT} tokens to program account...`);
+      await mintTo(
+        provider.connection,
+        wallet,
+        mint,
+        programTokenAccount,
+        wallet.publicKey,
+        MINT_AMOUNT
+      );
+
+      const tokenAccount = await getAccount(
+        provider.connection,
+        programTokenAccount
+      );
+      console.log(`Program token account balance: ${tokenAccount.amount}\n`);
+    } catch (error) {
+      console.error("Error during token minting:", error);
+      throw error;
+    }
+
     try {
       console.log("\n--- Initializing sale configuration ---");
       const tokenPriceUsd = 0.005;
-      const mintDecimals = new anchor.BN(6);
       const tokenLimit = new anchor.BN(initialTokenLimit);
 
+      // Passing the funded program token account records its balance as the sale's opening inventory
       await program.methods
-        .initializeSale(tokenPriceUsd, mintDecimals, tokenLimit)
+        .initializeSale(tokenPriceUsd, tokenLimit)
         .accounts({
           authority: wallet.publicKey,
           saleConfig: saleConfig.publicKey,
           systemProgram: anchor.web3.SystemProgram.programId,
           recipient: recipient,
           tokenMint: mint,
+          programTokenAccount: programTokenAccount,
         })
         .signers([wallet, saleConfig])
         .rpc();
+
+      // Pool budgets are sized from the ledger, so the opening balance has to be on it
+      const saleState = await program.account.saleConfig.fetch(saleConfig.publicKey);
+      assert.equal(saleState.inventory.deposited.toNumber(), totalMonthlyLimits * 500);
       console.log("Sale configuration initialized successfully.\n");
     } catch (error) {
       console.error("Error during sale initialization:", error);
@@ -227,40 +265,15 @@ describe("token_biu", () => {
       console.error("Error creating buyer token account:", error);
       throw error;
     }
-
-    try {
-      console.log("\n--- Minting tokens to program token account ---");
-      // Add extra tokens for safety
-      const MINT_AMOUNT = totalMonthlyLimits * 500;
-
-      console.log(`Minting ${MINT_AMOUNT} tokens to program account...`);
-      await mintTo(
-        provider.connection,
-        wallet,
-        mint,
-        programTokenAccount,
-        wallet.publicKey,
-        MINT_AMOUNT
-      );
-
-      const tokenAccount = await getAccount(
-        provider.connection,
-        programTokenAccount
-      );
-      console.log(`Program token account balance: ${tokenAccount.amount}\n`);
-    } catch (error) {
-      console.error("Error during token minting:", error);
-      throw error;
-    }
   });
 
   const [monthlyLimitsAccount, monthlyLimitsBump] = anchor.web3.PublicKey.findProgramAddressSync(
-    [Buffer.from("monthly_limits_a")],
+    [Buffer.from("monthly_limits_a"), saleConfig.publicKey.toBuffer(), Buffer.from([0])],
     program.programId
   );
 
   const [walletPurchaseAccount] = anchor.web3.PublicKey.findProgramAddressSync(
-    [Buffer.from("wallet_purchase"), buyer.publicKey.toBuffer()],
+    [Buffer.from("wallet_purchase"), saleConfig.publicKey.toBuffer(), buyer.publicKey.toBuffer()],
     program.programId
   );
 
@@ -284,8 +297,33 @@ describe("token_biu", () => {
     console.log("Listener added")
 
     try {
+      await program.methods
+        .setPoolBudget({ sale: {} }, new anchor.BN(totalMonthlyLimits))
+        .accounts({
+          authority: wallet.publicKey,
+          saleConfig: saleConfig.publicKey,
+          mint: mint,
+        })
+        .signers([wallet])
+        .rpc();
+
+      await program.methods
+        .initializeMonthlyLimits({ sale: {} })
+        .accounts({
+          authority: wallet.publicKey,
+          saleConfig: saleConfig.publicKey,
+          monthlyLimits: monthlyLimitsAccount,
+          systemProgram: anchor.web3.SystemProgram.programId,
+        })
+        .signers([wallet])
+        .rpc();
+
       const tx = await program.methods
-        .setMonthlyLimits(monthlyLimits, bnTimestamps)
+        .setMonthlyLimits(monthlyLimits, { monthly: {} }, bnTimestamps[0], {
+          allowPastStart: true,
+          minTranche: null,
+          maxTranche: null,
+        })
         .accounts({
           authority: wallet.publicKey,
           saleConfig: saleConfig.publicKey,
@@ -413,18 +451,16 @@ describe("token_biu", () => {
             console.log("Withdrawing ", rem.toNumber() / 1e6, " tokens");
 
             const tx = await program.methods
-              .withdrawTokens(totalWithdrawAmount)
+              .withdrawTokens(totalWithdrawAmount, "Monthly treasury allocation")
               .accounts({
                 saleConfig: saleConfig.publicKey,
                 authority: wallet.publicKey,
-                monthlyLimits: monthlyLimitsAccount,
+                monthlyLimits: null,
                 programTokenAccount: programTokenAccount,
                 programSaleAuthority: programSaleAuthority,
                 mint: mint,
-                adminTokenAccount: adminTokenAccount,
-                systemProgram: anchor.web3.SystemProgram.programId,
+                destination: adminTokenAccount,
                 tokenProgram: TOKEN_PROGRAM_ID,
-                associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
               })
               .signers([wallet])
               .rpc();
@@ -524,6 +560,228 @@ describe("token_biu", () => {
     }
   });
 
+  it("Buys and claims on a Token-2022 mint with a transfer fee", async () => {
+    console.log("\n=======================================");
+    console.log("Buying and claiming on a Token-2022 sale...");
+    console.log("=======================================");
+
+    const mint2022 = Keypair.generate();
+    const sale2022 = Keypair.generate();
+    const mintLen = getMintLen([ExtensionType.TransferFeeConfig]);
+    const lamports = await provider.connection.getMinimumBalanceForRentExemption(mintLen);
+
+    // 1% transfer fee, withheld by the Token-2022 program on every transfer
+    const createMintTx = new anchor.web3.Transaction().add(
+      anchor.web3.SystemProgram.createAccount({
+        fromPubkey: wallet.publicKey,
+        newAccountPubkey: mint2022.publicKey,
+        space: mintLen,
+        lamports,
+        programId: TOKEN_2022_PROGRAM_ID,
+      }),
+      createInitializeTransferFeeConfigInstruction(
+        mint2022.publicKey,
+        wallet.publicKey,
+        wallet.publicKey,
+        100,
+        BigInt(1_000_000_000),
+        TOKEN_2022_PROGRAM_ID
+      ),
+      createInitializeMintInstruction(mint2022.publicKey, 6, wallet.publicKey, null, TOKEN_2022_PROGRAM_ID)
+    );
+    await provider.sendAndConfirm(createMintTx, [wallet, mint2022]);
+
+    // Every sale signs with its own authority, so the inventory sits in that authority's ATA
+    const [programSaleAuthority2022] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("SALE_AUTHORITY"), sale2022.publicKey.toBuffer()],
+      program.programId
+    );
+    const programTokenAccount2022 = getAssociatedTokenAddressSync(
+      mint2022.publicKey,
+      programSaleAuthority2022,
+      true,
+      TOKEN_2022_PROGRAM_ID
+    );
+    const ataTx = new anchor.web3.Transaction().add(
+      createAssociatedTokenAccountInstruction(
+        wallet.publicKey,
+        programTokenAccount2022,
+        programSaleAuthority2022,
+        mint2022.publicKey,
+        TOKEN_2022_PROGRAM_ID
+      )
+    );
+    await provider.sendAndConfirm(ataTx, [wallet]);
+
+    await mintTo(
+      provider.connection,
+      wallet,
+      mint2022.publicKey,
+      programTokenAccount2022,
+      wallet.publicKey,
+      totalMonthlyLimits,
+      [],
+      undefined,
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    await program.methods
+      .initializeSale(0.005, new anchor.BN(initialTokenLimit))
+      .accounts({
+        authority: wallet.publicKey,
+        saleConfig: sale2022.publicKey,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        recipient: recipient,
+        tokenMint: mint2022.publicKey,
+        programTokenAccount: programTokenAccount2022,
+      })
+      .signers([wallet, sale2022])
+      .rpc();
+
+    await program.methods
+      .setPoolBudget({ sale: {} }, new anchor.BN(totalMonthlyLimits))
+      .accounts({
+        authority: wallet.publicKey,
+        saleConfig: sale2022.publicKey,
+        mint: mint2022.publicKey,
+      })
+      .signers([wallet])
+      .rpc();
+
+    const monthlyLimits = monthlyValues.map(value => new anchor.BN(value * 1000000));
+    const report = await program.methods
+      .validateMonthlyLimits({ sale: {} }, monthlyLimits, { monthly: {} }, new anchor.BN(timestamps[0]), {
+        allowPastStart: true,
+        minTranche: null,
+        maxTranche: null,
+      })
+      .accounts({
+        saleConfig: sale2022.publicKey,
+      })
+      .view();
+
+    assert.isNull(report.error, "Schedule should fit the Token-2022 sale pool");
+    assert.equal(report.inventory.toNumber(), totalMonthlyLimits);
+
+    // The sale schedule stays disabled, so purchases are only bounded by the sale pool
+    const [monthlyLimits2022] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("monthly_limits_a"), sale2022.publicKey.toBuffer(), Buffer.from([0])],
+      program.programId
+    );
+    await program.methods
+      .initializeMonthlyLimits({ sale: {} })
+      .accounts({
+        authority: wallet.publicKey,
+        saleConfig: sale2022.publicKey,
+        monthlyLimits: monthlyLimits2022,
+        systemProgram: anchor.web3.SystemProgram.programId,
+      })
+      .signers([wallet])
+      .rpc();
+
+    // Half of each purchase is released at once, the rest is already vested and can be claimed right after
+    await program.methods
+      .setBuyerVesting({
+        enabled: true,
+        tgeBps: 5_000,
+        startTimestamp: new anchor.BN(Math.floor(Date.now() / 1000) - 60),
+        cliffSeconds: new anchor.BN(0),
+        durationSeconds: new anchor.BN(1),
+      })
+      .accounts({
+        saleConfig: sale2022.publicKey,
+        authority: wallet.publicKey,
+      })
+      .signers([wallet])
+      .rpc();
+
+    const [buyerVesting2022] = anchor.web3.PublicKey.findProgramAddressSync(
+      [Buffer.from("buyer_vesting"), sale2022.publicKey.toBuffer(), buyer.publicKey.toBuffer()],
+      program.programId
+    );
+    const buyerTokenAccount2022 = getAssociatedTokenAddressSync(
+      mint2022.publicKey,
+      buyer.publicKey,
+      false,
+      TOKEN_2022_PROGRAM_ID
+    );
+    const solAmount = new anchor.BN(0.01 * LAMPORTS_PER_SOL);
+
+    const quote = await program.methods
+      .quotePurchase(solAmount, buyer.publicKey, null)
+      .accounts({
+        saleConfig: sale2022.publicKey,
+        mint: mint2022.publicKey,
+        walletPurchase: null,
+        buyerVesting: null,
+        monthlyLimits: monthlyLimits2022,
+        bonusLimits: null,
+        referralLimits: null,
+        referrerStats: null,
+        priceUpdate: SOL_USD_PRICE_UPDATE,
+      })
+      .view();
+
+    assert.isNull(quote.error, "Purchase should go through on the Token-2022 sale");
+    const tokensReleased = quote.tokenAmount.muln(5_000).divn(10_000);
+    const tokensLocked = quote.tokenAmount.sub(tokensReleased);
+
+    await program.methods
+      .buyTokens(solAmount)
+      .accounts({
+        buyer: buyer.publicKey,
+        saleAuthority: recipient,
+        programSaleAuthority: programSaleAuthority2022,
+        saleConfig: sale2022.publicKey,
+        authority: wallet.publicKey,
+        mint: mint2022.publicKey,
+        programTokenAccount: programTokenAccount2022,
+        buyerTokenAccount: buyerTokenAccount2022,
+        buyerVesting: buyerVesting2022,
+        monthlyLimits: monthlyLimits2022,
+        bonusLimits: null,
+        referralLimits: null,
+        proceedsVault: null,
+        referrer: null,
+        referrerStats: null,
+        referrerTokenAccount: null,
+        priceUpdate: SOL_USD_PRICE_UPDATE,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+      })
+      .signers([buyer])
+      .rpc();
+
+    // The sale pays the fee on top, the buyer is credited the full released share
+    let buyerAccount = await getAccount(provider.connection, buyerTokenAccount2022, undefined, TOKEN_2022_PROGRAM_ID);
+    assert.equal(buyerAccount.amount.toString(), tokensReleased.toString());
+
+    const position = await program.account.buyerVesting.fetch(buyerVesting2022);
+    assert.equal(position.totalLocked.toString(), tokensLocked.toString());
+
+    await program.methods
+      .claim()
+      .accounts({
+        owner: buyer.publicKey,
+        buyerVesting: buyerVesting2022,
+        saleConfig: sale2022.publicKey,
+        mint: mint2022.publicKey,
+        programSaleAuthority: programSaleAuthority2022,
+        programTokenAccount: programTokenAccount2022,
+        ownerTokenAccount: buyerTokenAccount2022,
+        systemProgram: anchor.web3.SystemProgram.programId,
+        tokenProgram: TOKEN_2022_PROGRAM_ID,
+        associatedTokenProgram: ASSOCIATED_PROGRAM_ID,
+      })
+      .signers([buyer])
+      .rpc();
+
+    // The claim is grossed up as well, so the buyer ends up with the whole purchase
+    buyerAccount = await getAccount(provider.connection, buyerTokenAccount2022, undefined, TOKEN_2022_PROGRAM_ID);
+    assert.equal(buyerAccount.amount.toString(), quote.tokenAmount.toString());
+  });
+
   async function testMonthChange(expectedMonth: number, newTimestamp: anchor.BN) {
     const smallPurchase = new anchor.BN(0.001 * LAMPORTS_PER_SOL);
 